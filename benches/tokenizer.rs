@@ -0,0 +1,36 @@
+//! Tokenizes generated scripts of doubling size, the time per byte should stay
+//! roughly flat as the source grows. Run with `cargo bench --bench tokenizer`.
+use std::time::{Duration, Instant};
+
+use event_script::event_script::tokenizer::Lexer;
+
+const SNIPPET: &str = "fn add_flags(a: u32, b: u32): u32 { return (a | b) & ~0x0f_u32; }\n\
+    let mut total = 0; // running total\n\
+    for i in 0..=100 { if i % 2 == 0 && i >= 10 { total += add_flags(i, 1_000) << 2; } }\n\
+    let ratio = 6.02e23 / 1.5; let name = \"event\";\n";
+
+const RUNS: usize = 5;
+
+fn median_time(source: &str) -> Duration {
+    let mut times: Vec<_> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            let tokens = Lexer::tokenize(source.to_string()).unwrap();
+            let elapsed = start.elapsed();
+            std::hint::black_box(tokens);
+            elapsed
+        })
+        .collect();
+    times.sort();
+    times[RUNS / 2]
+}
+
+fn main() {
+    println!("{:>10} {:>12} {:>10}", "bytes", "time", "ns/byte");
+    for kilobytes in [25, 50, 100, 200, 400, 800] {
+        let source = SNIPPET.repeat(kilobytes * 1024 / SNIPPET.len());
+        let time = median_time(&source);
+        let per_byte = time.as_secs_f64() * 1e9 / source.len() as f64;
+        println!("{:>10} {:>12.2?} {per_byte:>10.2}", source.len(), time);
+    }
+}
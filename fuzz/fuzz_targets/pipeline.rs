@@ -0,0 +1,38 @@
+//! Feeds arbitrary source through the lexer, parser and type checker and renders
+//! every error, none of which may panic.
+//!
+//! `cargo +nightly fuzz run pipeline fuzz/corpus/pipeline fuzz/regressions`
+//!
+//! Inputs that crashed are minimized with `cargo fuzz tmin` and added to
+//! `fuzz/regressions`, which `tests/robustness.rs` replays on every test run.
+#![no_main]
+
+use event_script::event_script::{
+    diagnostic::{Diagnostic, SourceMap},
+    parser::Parser,
+    tokenizer::Lexer,
+    type_system::TypeChecker,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+    let mut files = SourceMap::new();
+    let file = files.add("fuzz.es", source.to_string());
+    let tokens = match Lexer::tokenize_file(source.to_string(), file) {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            Diagnostic::from(&error).render(&files);
+            return;
+        }
+    };
+    let (statements, errors) = Parser::parse_recovering(tokens);
+    for error in &errors {
+        Diagnostic::from(error).render(&files);
+    }
+    if let Err(error) = TypeChecker::check(statements) {
+        Diagnostic::from(&error).render(&files);
+    }
+});
@@ -1,20 +1,131 @@
-use crate::event_script::token::{Token, TokenKind};
+use crate::event_script::{
+    span::Span,
+    token::{Token, TokenKind},
+};
+
+#[derive(Debug)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum Expression {
+pub enum ExpressionKind {
     String(String),
-    Number(String),
+    Number(String, Option<String>),
     Float(String),
+    Bool(bool),
     Groupping(Box<Expression>),
     Unary(Token, Box<Expression>),
     Binary(Box<Expression>, TokenKind, Box<Expression>),
-    Symbol(String),
-    Assignment(Box<Expression>),
-    // FunctionCall,
+    Symbol(Token),
+    Assignment(Box<Expression>, Box<Expression>),
+    // `a += b` keeps the binary operator applied to the target, `Plus` for `+=`.
+    CompoundAssignment(Box<Expression>, TokenKind, Box<Expression>),
+    // `++a` and `a--`, the operator is `PlusPlus` or `MinusMinus` and the flag marks the prefix form.
+    Increment(Box<Expression>, TokenKind, bool),
+    FunctionCall(Box<Expression>, Vec<Expression>),
+    Block(Vec<Statement>, Option<Box<Expression>>),
+    If(Box<Expression>, Box<Expression>, Box<Expression>),
+    // Source that failed to parse, its syntax error is already reported.
+    Error,
 }
+
 #[derive(Debug, PartialEq, Clone)]
-pub enum Statement {
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum StatementKind {
     Expression(Expression),
     Block(Vec<Statement>),
-    Variable(String, bool, bool, Option<String>, Option<Expression>),
+    Variable(Token, bool, bool, Option<String>, Option<Expression>),
+    Function(
+        String,
+        Vec<(String, String)>,
+        Option<String>,
+        Vec<Statement>,
+    ),
+    Return(Option<Expression>),
+    If(Expression, Vec<Statement>, Option<Box<Statement>>),
+    While(Expression, Vec<Statement>),
+    For(String, Expression, Vec<Statement>),
+    Break,
+    Continue,
+    // Source that failed to parse, its syntax error is already reported.
+    Error,
+}
+
+impl Expression {
+    #[must_use]
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+
+    /// Takes the kind out of the expression, its operands can't be moved out
+    /// of the expression itself because of the `Drop` implementation below.
+    #[must_use]
+    pub fn into_kind(mut self) -> ExpressionKind {
+        std::mem::replace(&mut self.kind, ExpressionKind::Error)
+    }
+}
+
+// Chains of binary operations like `a + b - c` aren't limited in length like
+// other nesting, so they are cloned and compared in a loop rather than recursively.
+impl Clone for Expression {
+    fn clone(&self) -> Self {
+        let mut operations = Vec::new();
+        let mut left = self;
+        while let ExpressionKind::Binary(lhs, operator, rhs) = &left.kind {
+            operations.push((operator, rhs, left.span));
+            left = lhs;
+        }
+        let mut expression = Expression::new(left.kind.clone(), left.span);
+        for (operator, rhs, span) in operations.into_iter().rev() {
+            let kind = ExpressionKind::Binary(Box::new(expression), operator.clone(), rhs.clone());
+            expression = Expression::new(kind, span);
+        }
+        expression
+    }
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut left, mut right) = (self, other);
+        loop {
+            match (&left.kind, &right.kind) {
+                (
+                    ExpressionKind::Binary(lhs, operator, rhs),
+                    ExpressionKind::Binary(other_lhs, other_operator, other_rhs),
+                ) => {
+                    if left.span != right.span || operator != other_operator || rhs != other_rhs {
+                        return false;
+                    }
+                    left = lhs;
+                    right = other_lhs;
+                }
+                _ => return left.span == right.span && left.kind == right.kind,
+            }
+        }
+    }
+}
+
+// Dropping the left operand of a chain would recurse once per operation, so
+// the chain is unlinked one operation at a time.
+impl Drop for Expression {
+    fn drop(&mut self) {
+        let mut kind = std::mem::replace(&mut self.kind, ExpressionKind::Error);
+        while let ExpressionKind::Binary(lhs, _, _) = &mut kind {
+            kind = std::mem::replace(&mut lhs.kind, ExpressionKind::Error);
+        }
+    }
+}
+
+impl Statement {
+    #[must_use]
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Statement { kind, span }
+    }
 }
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use crate::event_script::{token::TokenKind, value::Value};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum OpCode {
+    Constant(u32),
+    Pop,
+    GetLocal(u16),
+    SetLocal(u16),
+    ClearLocal(u16),
+    Unary(TokenKind),
+    Binary(TokenKind),
+    CheckBool,
+    Jump(u32),
+    JumpIfFalse(u32),
+    JumpIfTrue(u32),
+    /// Advances the iterator in the first slot, storing the item in the second
+    /// slot or jumping to the target once the iterator is exhausted.
+    IterNext(u16, u16, u32),
+    GetGlobal(u16),
+    SetGlobal(u16),
+    Unit,
+    Call(u8),
+    Return,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Chunk {
+    pub code: Vec<OpCode>,
+    pub constants: Vec<Value>,
+    pub locals_count: usize,
+    pub(crate) globals: HashMap<String, u16>,
+}
+
+impl Chunk {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn emit(&mut self, op: OpCode) -> usize {
+        self.code.push(op);
+        self.code.len() - 1
+    }
+
+    /// Rewrites the target of a previously emitted jump to the current end of the code.
+    pub fn patch_jump(&mut self, at: usize) {
+        let target = self.code.len() as u32;
+        match &mut self.code[at] {
+            OpCode::Jump(offset)
+            | OpCode::JumpIfFalse(offset)
+            | OpCode::JumpIfTrue(offset)
+            | OpCode::IterNext(_, _, offset) => {
+                *offset = target;
+            }
+            _ => {}
+        }
+    }
+}
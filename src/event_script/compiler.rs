@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::event_script::{
+    ast::{Expression, ExpressionKind, Statement, StatementKind},
+    bytecode::{Chunk, OpCode},
+    token::TokenKind,
+    value::{Callable, FunctionBody, Value},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum CompilerErrors {
+    VariableDoesntExist(String),
+    InvalidLiteral(String),
+    TooManyConstants,
+    TooManyLocals,
+    TooManyArguments,
+    ReturnInsideExpression,
+    InvalidAssignmentTarget,
+    BreakOutsideLoop,
+    ContinueOutsideLoop,
+    JumpTooLarge,
+    // The program still contains source that failed to parse.
+    SyntaxError,
+}
+
+impl Display for CompilerErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for CompilerErrors {}
+
+// `for` is a keyword, so scripts can never declare or read a local with this name.
+const ITERATOR_LOCAL: &str = "for";
+
+struct Local {
+    name: String,
+    depth: u16,
+}
+
+struct Loop {
+    start: usize,
+    breaks: Vec<usize>,
+}
+
+pub struct Compiler {
+    chunk: Chunk,
+    locals: Vec<Local>,
+    depth: u16,
+    expression_blocks: u16,
+    loops: Vec<Loop>,
+    // Slots of the script's top-level variables when compiling a function body.
+    globals: Option<HashMap<String, u16>>,
+}
+
+impl Compiler {
+    fn new() -> Self {
+        Self {
+            chunk: Chunk::new(),
+            locals: Vec::new(),
+            depth: 0,
+            expression_blocks: 0,
+            loops: Vec::new(),
+            globals: None,
+        }
+    }
+
+    /// Compiles a checked program into a chunk executable by the `Vm`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a variable can't be resolved, a literal is malformed
+    /// or the program exceeds the limits of the bytecode format.
+    pub fn compile(statements: &[Statement]) -> Result<Chunk, CompilerErrors> {
+        let mut compiler = Compiler::new();
+        for statement in statements {
+            compiler.compile_statement(statement)?;
+        }
+        Ok(compiler.chunk)
+    }
+
+    fn compile_statement(&mut self, statement: &Statement) -> Result<(), CompilerErrors> {
+        match &statement.kind {
+            StatementKind::Expression(expression) => {
+                self.compile_expression(expression)?;
+                self.chunk.emit(OpCode::Pop);
+            }
+            StatementKind::Block(statements) => self.compile_block(statements)?,
+            StatementKind::Variable(name, _, _, _, expression) => {
+                // The initializer is compiled before the slot is declared so that
+                // `let a = a;` reads the outer binding, same as the interpreter.
+                if let Some(expression) = expression {
+                    self.compile_expression(expression)?;
+                }
+                let slot = self.declare_local(&name.value)?;
+                if expression.is_some() {
+                    self.chunk.emit(OpCode::SetLocal(slot));
+                    self.chunk.emit(OpCode::Pop);
+                } else {
+                    self.chunk.emit(OpCode::ClearLocal(slot));
+                }
+            }
+            StatementKind::Function(name, params, _, body) => {
+                // Declared before the body so top-level functions can call themselves.
+                let slot = self.declare_local(name)?;
+                let function = self.compile_function(name, params, body)?;
+                self.emit_constant(function)?;
+                self.chunk.emit(OpCode::SetLocal(slot));
+                self.chunk.emit(OpCode::Pop);
+            }
+            StatementKind::Return(expression) => {
+                if self.expression_blocks > 0 {
+                    return Err(CompilerErrors::ReturnInsideExpression);
+                }
+                match expression {
+                    Some(expression) => self.compile_expression(expression)?,
+                    None => {
+                        self.chunk.emit(OpCode::Unit);
+                    }
+                }
+                self.chunk.emit(OpCode::Return);
+            }
+            StatementKind::If(condition, body, otherwise) => {
+                self.compile_expression(condition)?;
+                let else_jump = self.chunk.emit(OpCode::JumpIfFalse(0));
+                self.chunk.emit(OpCode::Pop);
+                self.compile_block(body)?;
+                let end_jump = self.chunk.emit(OpCode::Jump(0));
+                self.patch_jump(else_jump)?;
+                self.chunk.emit(OpCode::Pop);
+                if let Some(otherwise) = otherwise {
+                    self.compile_statement(otherwise)?;
+                }
+                self.patch_jump(end_jump)?;
+            }
+            StatementKind::While(condition, body) => {
+                let start = self.chunk.code.len();
+                self.compile_expression(condition)?;
+                let exit_jump = self.chunk.emit(OpCode::JumpIfFalse(0));
+                self.chunk.emit(OpCode::Pop);
+
+                self.loops.push(Loop {
+                    start,
+                    breaks: Vec::new(),
+                });
+                let result = self.compile_block(body);
+                let breaks = self.loops.pop().map(|lp| lp.breaks).unwrap_or_default();
+                result?;
+
+                self.emit_loop(start)?;
+                self.patch_jump(exit_jump)?;
+                self.chunk.emit(OpCode::Pop);
+                for jump in breaks {
+                    self.patch_jump(jump)?;
+                }
+            }
+            StatementKind::For(name, iterable, body) => {
+                self.compile_expression(iterable)?;
+                self.begin_scope();
+                let result = self.compile_for_loop(name, body);
+                self.end_scope();
+                result?;
+            }
+            StatementKind::Break => {
+                let jump = self.chunk.emit(OpCode::Jump(0));
+                self.loops
+                    .last_mut()
+                    .ok_or(CompilerErrors::BreakOutsideLoop)?
+                    .breaks
+                    .push(jump);
+            }
+            StatementKind::Continue => {
+                let start = self
+                    .loops
+                    .last()
+                    .ok_or(CompilerErrors::ContinueOutsideLoop)?
+                    .start;
+                self.emit_loop(start)?;
+            }
+            StatementKind::Error => return Err(CompilerErrors::SyntaxError),
+        }
+        Ok(())
+    }
+
+    fn compile_for_loop(&mut self, name: &str, body: &[Statement]) -> Result<(), CompilerErrors> {
+        let iterator = self.declare_local(ITERATOR_LOCAL)?;
+        self.chunk.emit(OpCode::SetLocal(iterator));
+        self.chunk.emit(OpCode::Pop);
+        let variable = self.declare_local(name)?;
+
+        let start = self.chunk.code.len();
+        let exit_jump = self.chunk.emit(OpCode::IterNext(iterator, variable, 0));
+        self.loops.push(Loop {
+            start,
+            breaks: Vec::new(),
+        });
+        let result = self.compile_block(body);
+        let breaks = self.loops.pop().map(|lp| lp.breaks).unwrap_or_default();
+        result?;
+
+        self.emit_loop(start)?;
+        self.patch_jump(exit_jump)?;
+        for jump in breaks {
+            self.patch_jump(jump)?;
+        }
+        Ok(())
+    }
+
+    fn emit_loop(&mut self, start: usize) -> Result<(), CompilerErrors> {
+        let start = u32::try_from(start).map_err(|_| CompilerErrors::JumpTooLarge)?;
+        self.chunk.emit(OpCode::Jump(start));
+        Ok(())
+    }
+
+    fn compile_block(&mut self, statements: &[Statement]) -> Result<(), CompilerErrors> {
+        self.begin_scope();
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.compile_statement(statement));
+        self.end_scope();
+        result
+    }
+
+    fn begin_scope(&mut self) {
+        self.depth += 1;
+    }
+
+    fn end_scope(&mut self) {
+        self.depth -= 1;
+        while self
+            .locals
+            .last()
+            .is_some_and(|local| local.depth > self.depth)
+        {
+            self.locals.pop();
+        }
+    }
+
+    fn compile_function(
+        &self,
+        name: &str,
+        params: &[(String, String)],
+        body: &[Statement],
+    ) -> Result<Value, CompilerErrors> {
+        let mut compiler = Compiler::new();
+        compiler.globals = Some(
+            self.globals
+                .clone()
+                .unwrap_or_else(|| self.chunk.globals.clone()),
+        );
+        for (param, _) in params {
+            compiler.declare_local(param)?;
+        }
+        for statement in body {
+            compiler.compile_statement(statement)?;
+        }
+        compiler.chunk.emit(OpCode::Unit);
+        compiler.chunk.emit(OpCode::Return);
+
+        Ok(Value::Function(Rc::new(Callable {
+            name: name.to_string(),
+            params: params.iter().map(|(param, _)| param.clone()).collect(),
+            body: FunctionBody::Bytecode(Rc::new(compiler.chunk)),
+        })))
+    }
+
+    fn compile_expression(&mut self, expression: &Expression) -> Result<(), CompilerErrors> {
+        match &expression.kind {
+            ExpressionKind::String(value) => self.emit_constant(Value::String(value.clone()))?,
+            ExpressionKind::Number(value, suffix) => {
+                self.emit_integer_literal(value, suffix, false)?;
+            }
+            ExpressionKind::Float(value) => {
+                let value = value
+                    .parse::<f64>()
+                    .map_err(|_| CompilerErrors::InvalidLiteral(value.clone()))?;
+                self.emit_constant(Value::Float(value))?;
+            }
+            ExpressionKind::Bool(value) => self.emit_constant(Value::Bool(*value))?,
+            ExpressionKind::Symbol(name) => {
+                let op = self.resolve(&name.value)?;
+                self.chunk.emit(op);
+            }
+            ExpressionKind::Groupping(expression) => self.compile_expression(expression)?,
+            ExpressionKind::Assignment(target, value) => {
+                let (_, set) = self.resolve_target(target)?;
+                self.compile_expression(value)?;
+                self.chunk.emit(set);
+            }
+            // Same order as the interpreter, the target is read before the value runs.
+            ExpressionKind::CompoundAssignment(target, operator, value) => {
+                let (get, set) = self.resolve_target(target)?;
+                self.chunk.emit(get);
+                self.compile_expression(value)?;
+                self.chunk.emit(OpCode::Binary(operator.clone()));
+                self.chunk.emit(set);
+            }
+            ExpressionKind::Increment(target, operator, prefix) => {
+                let (get, set) = self.resolve_target(target)?;
+                self.chunk.emit(get.clone());
+                if !*prefix {
+                    // Keep the old value below the updated one, which is dropped after the store.
+                    self.chunk.emit(get);
+                }
+                self.chunk.emit(OpCode::Unary(operator.clone()));
+                self.chunk.emit(set);
+                if !*prefix {
+                    self.chunk.emit(OpCode::Pop);
+                }
+            }
+            // Same as the interpreter, `-128i8` is a single constant.
+            ExpressionKind::Unary(operator, expression) => match &expression.kind {
+                ExpressionKind::Number(value, suffix) if operator.kind == TokenKind::Minus => {
+                    self.emit_integer_literal(value, suffix, true)?;
+                }
+                _ => {
+                    self.compile_expression(expression)?;
+                    self.chunk.emit(OpCode::Unary(operator.kind.clone()));
+                }
+            },
+            ExpressionKind::Binary(..) => self.compile_binary(expression)?,
+            ExpressionKind::FunctionCall(callee, arguments) => {
+                let count =
+                    u8::try_from(arguments.len()).map_err(|_| CompilerErrors::TooManyArguments)?;
+                self.compile_expression(callee)?;
+                for argument in arguments {
+                    self.compile_expression(argument)?;
+                }
+                self.chunk.emit(OpCode::Call(count));
+            }
+            ExpressionKind::Block(statements, tail) => {
+                let outer_loops = std::mem::take(&mut self.loops);
+                self.begin_scope();
+                self.expression_blocks += 1;
+                let result = statements
+                    .iter()
+                    .try_for_each(|statement| self.compile_statement(statement))
+                    .and_then(|()| match tail {
+                        Some(tail) => self.compile_expression(tail),
+                        None => {
+                            self.chunk.emit(OpCode::Unit);
+                            Ok(())
+                        }
+                    });
+                self.expression_blocks -= 1;
+                self.end_scope();
+                self.loops = outer_loops;
+                result?;
+            }
+            ExpressionKind::If(condition, body, otherwise) => {
+                self.compile_expression(condition)?;
+                let else_jump = self.chunk.emit(OpCode::JumpIfFalse(0));
+                self.chunk.emit(OpCode::Pop);
+                self.compile_expression(body)?;
+                let end_jump = self.chunk.emit(OpCode::Jump(0));
+                self.patch_jump(else_jump)?;
+                self.chunk.emit(OpCode::Pop);
+                self.compile_expression(otherwise)?;
+                self.patch_jump(end_jump)?;
+            }
+            ExpressionKind::Error => return Err(CompilerErrors::SyntaxError),
+        }
+        Ok(())
+    }
+
+    /// Compiles a chain of binary operations like `a + b - c` from the
+    /// innermost one outwards, same as the interpreter evaluates it.
+    fn compile_binary(&mut self, expression: &Expression) -> Result<(), CompilerErrors> {
+        let mut operations = Vec::new();
+        let mut left = expression;
+        while let ExpressionKind::Binary(lhs, operator, rhs) = &left.kind {
+            operations.push((operator, rhs));
+            left = lhs;
+        }
+        self.compile_expression(left)?;
+        for (operator, rhs) in operations.into_iter().rev() {
+            match operator {
+                TokenKind::And => {
+                    let jump = self.chunk.emit(OpCode::JumpIfFalse(0));
+                    self.compile_short_circuit_tail(jump, rhs)?;
+                }
+                TokenKind::Or => {
+                    let jump = self.chunk.emit(OpCode::JumpIfTrue(0));
+                    self.compile_short_circuit_tail(jump, rhs)?;
+                }
+                _ => {
+                    self.compile_expression(rhs)?;
+                    self.chunk.emit(OpCode::Binary(operator.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn compile_short_circuit_tail(
+        &mut self,
+        jump: usize,
+        rhs: &Expression,
+    ) -> Result<(), CompilerErrors> {
+        self.chunk.emit(OpCode::Pop);
+        self.compile_expression(rhs)?;
+        self.chunk.emit(OpCode::CheckBool);
+        self.patch_jump(jump)
+    }
+
+    fn patch_jump(&mut self, at: usize) -> Result<(), CompilerErrors> {
+        if u32::try_from(self.chunk.code.len()).is_err() {
+            return Err(CompilerErrors::JumpTooLarge);
+        }
+        self.chunk.patch_jump(at);
+        Ok(())
+    }
+
+    fn emit_constant(&mut self, value: Value) -> Result<(), CompilerErrors> {
+        let index = u32::try_from(self.chunk.constants.len())
+            .map_err(|_| CompilerErrors::TooManyConstants)?;
+        self.chunk.constants.push(value);
+        self.chunk.emit(OpCode::Constant(index));
+        Ok(())
+    }
+
+    fn emit_integer_literal(
+        &mut self,
+        value: &str,
+        suffix: &Option<String>,
+        negative: bool,
+    ) -> Result<(), CompilerErrors> {
+        let value = Value::integer_literal(value, suffix.as_deref(), negative)
+            .ok_or_else(|| CompilerErrors::InvalidLiteral(value.to_string()))?;
+        self.emit_constant(value)
+    }
+
+    fn declare_local(&mut self, name: &str) -> Result<u16, CompilerErrors> {
+        // A redeclared global keeps its slot, functions compiled before it read
+        // the new value just like the interpreter's lookup by name.
+        if self.depth == 0
+            && self.globals.is_none()
+            && let Some(&slot) = self.chunk.globals.get(name)
+        {
+            return Ok(slot);
+        }
+        let slot = u16::try_from(self.locals.len()).map_err(|_| CompilerErrors::TooManyLocals)?;
+        self.locals.push(Local {
+            name: name.to_string(),
+            depth: self.depth,
+        });
+        self.chunk.locals_count = self.chunk.locals_count.max(self.locals.len());
+        if self.depth == 0 && self.globals.is_none() {
+            self.chunk.globals.insert(name.to_string(), slot);
+        }
+        Ok(slot)
+    }
+
+    fn resolve(&self, name: &str) -> Result<OpCode, CompilerErrors> {
+        if let Some(slot) = self.locals.iter().rposition(|local| local.name == name) {
+            let slot = u16::try_from(slot).map_err(|_| CompilerErrors::TooManyLocals)?;
+            return Ok(OpCode::GetLocal(slot));
+        }
+        self.global_slot(name).map(OpCode::GetGlobal)
+    }
+
+    /// Returns the instructions reading and writing an assignment target.
+    fn resolve_target(&self, target: &Expression) -> Result<(OpCode, OpCode), CompilerErrors> {
+        let ExpressionKind::Symbol(name) = &target.kind else {
+            return Err(CompilerErrors::InvalidAssignmentTarget);
+        };
+        match self.resolve(&name.value)? {
+            OpCode::GetLocal(slot) => Ok((OpCode::GetLocal(slot), OpCode::SetLocal(slot))),
+            get => Ok((get, OpCode::SetGlobal(self.global_slot(&name.value)?))),
+        }
+    }
+
+    fn global_slot(&self, name: &str) -> Result<u16, CompilerErrors> {
+        self.globals
+            .as_ref()
+            .and_then(|globals| globals.get(name))
+            .copied()
+            .ok_or(CompilerErrors::VariableDoesntExist(name.to_string()))
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt::{Display, Write};
+
+use crate::event_script::span::{FileId, Span};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A message attached to a piece of source.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// An error or warning about a script, with everything needed to render it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable identifier of the kind of problem, e.g. `E0301`.
+    pub code: &'static str,
+    pub message: String,
+    /// Where the problem is, drawn with `^`.
+    pub primary: Option<Label>,
+    /// Related places, drawn with `-`.
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    #[must_use]
+    pub fn new<S: ToString>(severity: Severity, code: &'static str, message: &S) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            message: message.to_string(),
+            primary: None,
+            secondary: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    #[must_use]
+    pub fn error<S: ToString>(code: &'static str, message: &S) -> Self {
+        Self::new(Severity::Error, code, message)
+    }
+
+    #[must_use]
+    pub fn with_primary<S: ToString>(mut self, span: Span, message: &S) -> Self {
+        self.primary = Some(Label {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn with_secondary<S: ToString>(mut self, span: Span, message: &S) -> Self {
+        self.secondary.push(Label {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn with_note<S: ToString>(mut self, note: &S) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn with_help<S: ToString>(mut self, help: &S) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    /// Renders the diagnostic for a terminal, quoting the labelled source lines:
+    ///
+    /// ```text
+    /// error[E0324]: mismatched types
+    ///  --> main.es:1:13
+    ///   |
+    /// 1 | let a: u8 = 1.5;
+    ///   |             ^^^ expected `u8`, found `f32`
+    /// ```
+    #[must_use]
+    pub fn render(&self, files: &SourceMap) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}[{}]: {}", self.severity, self.code, self.message);
+
+        let labels: Vec<_> = self
+            .primary
+            .iter()
+            .map(|label| (label, '^'))
+            .chain(self.secondary.iter().map(|label| (label, '-')))
+            .collect();
+        let file = labels
+            .first()
+            .and_then(|(label, _)| files.get(label.span.file));
+        let Some(file) = file else {
+            for note in &self.notes {
+                let _ = writeln!(out, "= note: {note}");
+            }
+            if let Some(help) = &self.help {
+                let _ = writeln!(out, "= help: {help}");
+            }
+            return out;
+        };
+
+        // Labels pointing into other files are left out of the snippet.
+        let mut rows: Vec<_> = labels
+            .iter()
+            .filter(|(label, _)| label.span.file == labels[0].0.span.file)
+            .map(|(label, marker)| {
+                let (line, column) = file.location(label.span.start);
+                let text = file.line(line);
+                let line_end = text.chars().count() + 1;
+                let end_column = if file.location(label.span.end).0 == line {
+                    file.location(label.span.end).1
+                } else {
+                    line_end
+                };
+                let width = end_column.saturating_sub(column).max(1);
+                (line, column, width, *marker, label.message.as_str())
+            })
+            .collect();
+        let (first_line, first_column) = (rows[0].0, rows[0].1);
+        rows.sort_by_key(|(line, ..)| *line);
+        let gutter = rows.last().map_or(1, |(line, ..)| line.to_string().len());
+
+        let _ = writeln!(
+            out,
+            "{:gutter$}--> {}:{first_line}:{first_column}",
+            "", file.name
+        );
+        let _ = writeln!(out, "{:gutter$} |", "");
+        let mut previous_line: Option<usize> = None;
+        for (index, (line, ..)) in rows.iter().enumerate() {
+            if previous_line == Some(*line) {
+                continue;
+            }
+            if previous_line.is_some_and(|previous| *line > previous + 1) {
+                let _ = writeln!(out, "...");
+            }
+            let _ = writeln!(out, "{line:>gutter$} | {}", file.line(*line));
+            for (_, column, width, marker, message) in
+                rows[index..].iter().take_while(|row| row.0 == *line)
+            {
+                // Built with `repeat`, format widths can't exceed `u16::MAX`
+                // and a generated line can be longer.
+                let indent = " ".repeat(column - 1);
+                let underline = marker.to_string().repeat(*width);
+                let row = format!("{:gutter$} | {indent}{underline} {message}", "");
+                let _ = writeln!(out, "{}", row.trim_end());
+            }
+            previous_line = Some(*line);
+        }
+
+        if !self.notes.is_empty() || self.help.is_some() {
+            let _ = writeln!(out, "{:gutter$} |", "");
+        }
+        for note in &self.notes {
+            let _ = writeln!(out, "{:gutter$} = note: {note}", "");
+        }
+        if let Some(help) = &self.help {
+            let _ = writeln!(out, "{:gutter$} = help: {help}", "");
+        }
+        out
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+/// A named script and the byte offsets its lines start at.
+#[derive(Debug)]
+pub struct SourceFile {
+    pub name: String,
+    pub source: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    #[must_use]
+    pub fn new(name: &str, source: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        SourceFile {
+            name: name.to_string(),
+            source,
+            line_starts,
+        }
+    }
+
+    /// 1-based line and column, counted in chars, of a byte offset.
+    #[must_use]
+    pub fn location(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self
+            .source
+            .get(line_start..offset)
+            .map_or(0, |text| text.chars().count());
+        (line, column + 1)
+    }
+
+    /// Text of a 1-based line without its line break.
+    #[must_use]
+    pub fn line(&self, line: usize) -> &str {
+        let start = self.line_starts[line - 1];
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.source.len(), |next| next - 1);
+        self.source[start..end].trim_end_matches('\r')
+    }
+}
+
+/// Every script loaded so far, spans refer to them by `FileId`.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: &str, source: String) -> FileId {
+        self.files.push(SourceFile::new(name, source));
+        self.files.len() - 1
+    }
+
+    #[must_use]
+    pub fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file)
+    }
+}
@@ -1,94 +1,246 @@
 use super::parser::{Parser, ParserErrors};
-use super::token::TokenKind;
-use crate::event_script::ast::Expression;
+use super::token::{Associativity, PREFIX_BINDING_POWER, TokenKind};
+use crate::event_script::{
+    ast::{Expression, ExpressionKind, Statement, StatementKind},
+    span::Span,
+};
 
 impl Parser {
-    pub(super) fn parse_unary_expression(&mut self) -> Result<Expression, ParserErrors> {
-        use super::token::TokenKind::{Minus, Not};
+    pub(super) fn parse_unary_expression(&mut self) -> Result<ExpressionKind, ParserErrors> {
+        use super::token::TokenKind::{Minus, Not, Tilde};
         let operator = self.next_token()?;
         match operator.kind {
-            Minus | Not => {}
+            Minus | Not | Tilde => {}
             _ => return Err(ParserErrors::UnexpectedTokenKind(operator)),
         }
-        let expression = self.parse_expression(0)?;
+        let expression = self.parse_expression(PREFIX_BINDING_POWER)?;
 
-        Ok(Expression::Unary(operator, Box::new(expression)))
+        Ok(ExpressionKind::Unary(operator, Box::new(expression)))
     }
 
-    pub(super) fn parse_groupping_expression(&mut self) -> Result<Expression, ParserErrors> {
+    pub(super) fn parse_groupping_expression(&mut self) -> Result<ExpressionKind, ParserErrors> {
         self.expect_token(&TokenKind::OpenParen)?;
         let inner = self.parse_expression(0)?;
         self.expect_token(&TokenKind::CloseParen)?;
 
-        Ok(Expression::Groupping(Box::new(inner)))
+        Ok(ExpressionKind::Groupping(Box::new(inner)))
     }
 
     pub(super) fn parse_binary_expression(
         &mut self,
         left: Expression,
         power: u8,
-    ) -> Result<Expression, ParserErrors> {
+    ) -> Result<ExpressionKind, ParserErrors> {
         let op_token = self.next_token()?;
-        let right = self.parse_expression(power)?;
+        if op_token.kind.get_associativity() == Associativity::None
+            && matches!(
+                &left.kind,
+                ExpressionKind::Binary(_, kind, _) if kind.get_binding_power() == power
+            )
+        {
+            return Err(ParserErrors::NonAssociativeOperator(op_token));
+        }
+        let right = self.parse_expression(Self::right_binding_power(&op_token.kind, power))?;
 
-        Ok(Expression::Binary(
+        Ok(ExpressionKind::Binary(
             Box::new(left),
             op_token.kind.clone(),
             Box::new(right),
         ))
     }
 
-    pub(super) fn parse_primary_expression(&mut self) -> Result<Expression, ParserErrors> {
-        use super::token::TokenKind::{Identifier, Number, String};
+    pub(super) fn parse_primary_expression(&mut self) -> Result<ExpressionKind, ParserErrors> {
+        use super::token::TokenKind::{False, Float, Identifier, Number, String, True};
         let next_token = self.next_token()?;
         match next_token.kind {
-            Number => Ok(Expression::Number(next_token.value)),
-            String => Ok(Expression::String(next_token.value)),
-            Identifier => Ok(Expression::Symbol(next_token.value)),
-            _ => {
-                panic!("{next_token:#?} \n Should never happen")
-            }
+            Number => Ok(ExpressionKind::Number(next_token.value, next_token.suffix)),
+            Float => Ok(ExpressionKind::Float(next_token.value)),
+            True => Ok(ExpressionKind::Bool(true)),
+            False => Ok(ExpressionKind::Bool(false)),
+            String => Ok(ExpressionKind::String(next_token.value)),
+            Identifier => Ok(ExpressionKind::Symbol(next_token)),
+            _ => Err(ParserErrors::NoFunctionHandler(next_token)),
         }
     }
 
     pub(super) fn parse_assignment_expression(
         &mut self,
+        target: Expression,
         power: u8,
-    ) -> Result<Expression, ParserErrors> {
-        self.next_token()?;
-        let value = self.parse_expression(power)?;
+    ) -> Result<ExpressionKind, ParserErrors> {
+        let operator = self.next_token()?;
+        if !Self::is_assignable(&target) {
+            return Err(ParserErrors::InvalidAssignmentTarget(operator));
+        }
+        // Assignment is right associative, `a = b = c` assigns `c` to both.
+        let value = self.parse_expression(Self::right_binding_power(&operator.kind, power))?;
+
+        Ok(ExpressionKind::Assignment(
+            Box::new(target),
+            Box::new(value),
+        ))
+    }
+
+    pub(super) fn parse_compound_assignment_expression(
+        &mut self,
+        target: Expression,
+        power: u8,
+    ) -> Result<ExpressionKind, ParserErrors> {
+        let operator = self.next_token()?;
+        let binary_operator = match operator.kind {
+            TokenKind::PlusEquals => TokenKind::Plus,
+            TokenKind::MinusEquals => TokenKind::Minus,
+            TokenKind::MultiplyEquals => TokenKind::Star,
+            TokenKind::DivideEquals => TokenKind::Slash,
+            TokenKind::ModEquals => TokenKind::Percent,
+            _ => return Err(ParserErrors::UnexpectedTokenKind(operator)),
+        };
+        if !Self::is_assignable(&target) {
+            return Err(ParserErrors::InvalidAssignmentTarget(operator));
+        }
+        let value = self.parse_expression(Self::right_binding_power(&operator.kind, power))?;
+
+        Ok(ExpressionKind::CompoundAssignment(
+            Box::new(target),
+            binary_operator,
+            Box::new(value),
+        ))
+    }
+
+    pub(super) fn parse_prefix_increment_expression(
+        &mut self,
+    ) -> Result<ExpressionKind, ParserErrors> {
+        let operator =
+            self.expect_any_token(&vec![&TokenKind::PlusPlus, &TokenKind::MinusMinus])?;
+        let target = self.parse_expression(operator.binding_power)?;
+        if !Self::is_assignable(&target) {
+            return Err(ParserErrors::InvalidAssignmentTarget(operator));
+        }
+
+        Ok(ExpressionKind::Increment(
+            Box::new(target),
+            operator.kind,
+            true,
+        ))
+    }
+
+    pub(super) fn parse_postfix_increment_expression(
+        &mut self,
+        target: Expression,
+    ) -> Result<ExpressionKind, ParserErrors> {
+        let operator =
+            self.expect_any_token(&vec![&TokenKind::PlusPlus, &TokenKind::MinusMinus])?;
+        if !Self::is_assignable(&target) {
+            return Err(ParserErrors::InvalidAssignmentTarget(operator));
+        }
+
+        Ok(ExpressionKind::Increment(
+            Box::new(target),
+            operator.kind,
+            false,
+        ))
+    }
+
+    /// Whether an expression names a place that can be assigned to.
+    fn is_assignable(expression: &Expression) -> bool {
+        matches!(expression.kind, ExpressionKind::Symbol(_))
+    }
+
+    pub(super) fn parse_if_expression(&mut self) -> Result<ExpressionKind, ParserErrors> {
+        self.parse_if(true)
+    }
+
+    /// Parses an `if` chain. Where the chain starts a statement `else_required`
+    /// is false and a missing `else` stands for an empty block, so the chain
+    /// evaluates to `()` like an `if` statement.
+    pub(super) fn parse_if(&mut self, else_required: bool) -> Result<ExpressionKind, ParserErrors> {
+        self.expect_token(&TokenKind::If)?;
+        let condition = self.parse_expression(0)?;
+        let body = self.parse_spanned(Self::parse_block_expression)?;
+
+        let otherwise = if else_required || self.current_token().kind == TokenKind::Else {
+            self.expect_token(&TokenKind::Else)?;
+            if self.current_token().kind == TokenKind::If {
+                self.parse_spanned(|parser| parser.parse_if(else_required))?
+            } else {
+                self.parse_spanned(Self::parse_block_expression)?
+            }
+        } else {
+            let end = Span::new(body.span.file, body.span.end, body.span.end);
+            Expression::new(ExpressionKind::Block(Vec::new(), None), end)
+        };
+
+        Ok(ExpressionKind::If(
+            Box::new(condition),
+            Box::new(body),
+            Box::new(otherwise),
+        ))
+    }
+
+    /// Parses `{ statements... tail }` where the optional tail expression,
+    /// written without a trailing semicolon, is the value of the block.
+    pub(super) fn parse_block_expression(&mut self) -> Result<ExpressionKind, ParserErrors> {
+        self.expect_token(&TokenKind::OpenCurly)?;
+        let mut statements = Vec::new();
+        let mut tail = None;
+        while !matches!(
+            self.current_token().kind,
+            TokenKind::CloseCurly | TokenKind::Eof
+        ) {
+            // Unlike other statements, an `if` can be the value of the block.
+            let is_if = self.current_token().kind == TokenKind::If;
+            if !is_if && self.has_statement_handler(&self.current_token().kind) {
+                statements.push(self.parse_statement_recovering());
+                continue;
+            }
+            let start = self.current_token().span();
+            let remaining = self.tokens_left();
+            let parsed = if is_if {
+                self.parse_if_statement_expression()
+            } else {
+                self.parse_expression(0)
+            };
+            let expression = match parsed {
+                Ok(expression) => expression,
+                Err(error) => {
+                    statements.push(self.recover_statement(error, start, remaining));
+                    continue;
+                }
+            };
+            if self.current_token().kind == TokenKind::CloseCurly {
+                tail = Some(Box::new(expression));
+                break;
+            }
+            // An `if` ends with a block, like an `if` statement it needs no `;`.
+            if !is_if {
+                self.expect_semicolon();
+            } else if self.current_token().kind == TokenKind::SemiColon {
+                self.next_token()?;
+            }
+            let span = self.span_from(expression.span);
+            statements.push(Statement::new(StatementKind::Expression(expression), span));
+        }
+        self.expect_token(&TokenKind::CloseCurly)?;
 
-        Ok(Expression::Assignment(Box::new(value)))
+        Ok(ExpressionKind::Block(statements, tail))
     }
 
-    // pub(super) fn parse_function_call(
-    //     &mut self,
-    //     left: Expression,
-    //     power: u8,
-    // ) -> Result<Expression, ParserErrors> {
-    //     let mut params = vec![];
-    //
-    //     self.expect_token(TokenKind::OpenParen)?;
-    //
-    //     let mut has_args = false;
-    //     if let Err(e) = self.expect_token(TokenKind::CloseParen) {
-    //         match e {
-    //             ParserErrors::UnexpectedTokenKind(_) => has_args = true,
-    //             _ => return Err(e),
-    //         }
-    //     }
-    //
-    //     if has_args {
-    //         params.push(self.parse_expression(power.clone())?);
-    //         while let Ok(_) = self.expect_token(TokenKind::Comma) {
-    //             params.push(self.parse_expression(0)?);
-    //         }
-    //     }
-    //
-    //     self.expect_token(TokenKind::CloseParen)?;
-    //     Ok(Box::new(FunctionCallExpression {
-    //         params,
-    //         identifier: left,
-    //     }))
-    // }
+    pub(super) fn parse_function_call(
+        &mut self,
+        left: Expression,
+    ) -> Result<ExpressionKind, ParserErrors> {
+        let mut params = vec![];
+
+        self.expect_token(&TokenKind::OpenParen)?;
+        if self.current_token().kind != TokenKind::CloseParen {
+            params.push(self.parse_expression(0)?);
+            while self.current_token().kind == TokenKind::Comma {
+                self.next_token()?;
+                params.push(self.parse_expression(0)?);
+            }
+        }
+        self.expect_token(&TokenKind::CloseParen)?;
+
+        Ok(ExpressionKind::FunctionCall(Box::new(left), params))
+    }
 }
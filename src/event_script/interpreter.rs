@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::event_script::{
+    ast::{Expression, ExpressionKind, Statement, StatementKind},
+    token::TokenKind,
+    value::{Callable, FunctionBody, Value},
+};
+
+/// Maximum number of nested calls before a script is aborted, shared by both backends.
+pub const MAX_CALL_DEPTH: usize = 128;
+
+#[derive(Debug, PartialEq)]
+pub enum RuntimeErrors {
+    VariableDoesntExist(String),
+    UninitializedVariable(String),
+    InvalidLiteral(String),
+    UnsupportedUnaryOperation(TokenKind, &'static str),
+    UnsupportedBinaryOperation(TokenKind, &'static str, &'static str),
+    ExpectedBool(&'static str),
+    NotCallable(&'static str),
+    NotIterable(&'static str),
+    ArgumentCountMismatch(String, usize, usize),
+    ControlFlowInsideExpression,
+    InvalidAssignmentTarget,
+    IntegerOverflow,
+    DivisionByZero,
+    StackOverflow,
+    // The program still contains source that failed to parse.
+    SyntaxError,
+}
+
+impl Display for RuntimeErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for RuntimeErrors {}
+
+#[derive(Debug)]
+pub struct Environment {
+    scopes: Vec<HashMap<String, Option<Value>>>,
+}
+
+impl Environment {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    pub fn define(&mut self, name: &str, value: Option<Value>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+
+    /// Looks a variable up from the innermost scope outwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable is not declared or has not been assigned yet.
+    pub fn get(&self, name: &str) -> Result<&Value, RuntimeErrors> {
+        match self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            Some(Some(value)) => Ok(value),
+            Some(None) => Err(RuntimeErrors::UninitializedVariable(name.to_string())),
+            None => Err(RuntimeErrors::VariableDoesntExist(name.to_string())),
+        }
+    }
+
+    /// Overwrites the innermost variable with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable is not declared.
+    pub fn assign(&mut self, name: &str, value: Value) -> Result<(), RuntimeErrors> {
+        let slot = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+            .ok_or(RuntimeErrors::VariableDoesntExist(name.to_string()))?;
+        *slot = Some(value);
+        Ok(())
+    }
+
+    /// Hides every scope except the global one for the duration of a call and
+    /// returns the hidden scopes so they can be restored by `leave_function`.
+    pub fn enter_function(&mut self) -> Vec<HashMap<String, Option<Value>>> {
+        let outer = self.scopes.split_off(1);
+        self.push_scope();
+        outer
+    }
+
+    pub fn leave_function(&mut self, outer: Vec<HashMap<String, Option<Value>>>) {
+        self.scopes.truncate(1);
+        self.scopes.extend(outer);
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum Flow {
+    Normal,
+    Return(Value),
+    Break,
+    Continue,
+}
+
+pub struct Interpreter {
+    environment: Environment,
+    call_depth: usize,
+}
+
+impl Interpreter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            environment: Environment::new(),
+            call_depth: 0,
+        }
+    }
+
+    /// Executes the statements in the global scope of the interpreter.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first runtime error raised by a statement.
+    pub fn run(&mut self, statements: &[Statement]) -> Result<(), RuntimeErrors> {
+        self.execute_all(statements)?;
+        Ok(())
+    }
+
+    /// Returns the value of a variable visible from the current scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable is not declared or not initialized.
+    pub fn get_variable(&self, name: &str) -> Result<&Value, RuntimeErrors> {
+        self.environment.get(name)
+    }
+
+    fn execute_all(&mut self, statements: &[Statement]) -> Result<Flow, RuntimeErrors> {
+        for statement in statements {
+            let flow = self.execute(statement)?;
+            if !matches!(flow, Flow::Normal) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn execute(&mut self, statement: &Statement) -> Result<Flow, RuntimeErrors> {
+        match &statement.kind {
+            StatementKind::Expression(expression) => {
+                self.evaluate(expression)?;
+            }
+            StatementKind::Block(statements) => return self.execute_block(statements),
+            StatementKind::Variable(name, _, _, _, expression) => {
+                let value = match expression {
+                    Some(expression) => Some(self.evaluate(expression)?),
+                    None => None,
+                };
+                self.environment.define(&name.value, value);
+            }
+            StatementKind::Function(name, params, _, body) => {
+                let function = Callable {
+                    name: name.clone(),
+                    params: params.iter().map(|(param, _)| param.clone()).collect(),
+                    body: FunctionBody::Ast(body.clone()),
+                };
+                self.environment
+                    .define(name, Some(Value::Function(Rc::new(function))));
+            }
+            StatementKind::Return(expression) => {
+                let value = match expression {
+                    Some(expression) => self.evaluate(expression)?,
+                    None => Value::Unit,
+                };
+                return Ok(Flow::Return(value));
+            }
+            StatementKind::If(condition, body, otherwise) => {
+                if self.evaluate(condition)?.as_bool()? {
+                    return self.execute_block(body);
+                }
+                if let Some(otherwise) = otherwise {
+                    return self.execute(otherwise);
+                }
+            }
+            StatementKind::While(condition, body) => {
+                while self.evaluate(condition)?.as_bool()? {
+                    match self.execute_block(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+            }
+            StatementKind::For(name, iterable, body) => {
+                let mut iterator = self.evaluate(iterable)?;
+                while let Some((item, rest)) = iterator.next_item()? {
+                    iterator = rest;
+                    self.environment.push_scope();
+                    self.environment.define(name, Some(item));
+                    let flow = self.execute_block(body);
+                    self.environment.pop_scope();
+                    match flow? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+            }
+            StatementKind::Break => return Ok(Flow::Break),
+            StatementKind::Continue => return Ok(Flow::Continue),
+            StatementKind::Error => return Err(RuntimeErrors::SyntaxError),
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn execute_block(&mut self, statements: &[Statement]) -> Result<Flow, RuntimeErrors> {
+        self.environment.push_scope();
+        let result = self.execute_all(statements);
+        self.environment.pop_scope();
+        result
+    }
+
+    fn evaluate_block(
+        &mut self,
+        statements: &[Statement],
+        tail: Option<&Expression>,
+    ) -> Result<Value, RuntimeErrors> {
+        if !matches!(self.execute_all(statements)?, Flow::Normal) {
+            return Err(RuntimeErrors::ControlFlowInsideExpression);
+        }
+        match tail {
+            Some(tail) => self.evaluate(tail),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    fn call(&mut self, function: &Callable, arguments: Vec<Value>) -> Result<Value, RuntimeErrors> {
+        let FunctionBody::Ast(body) = &function.body else {
+            return Err(RuntimeErrors::NotCallable("function"));
+        };
+        if function.params.len() != arguments.len() {
+            return Err(RuntimeErrors::ArgumentCountMismatch(
+                function.name.clone(),
+                function.params.len(),
+                arguments.len(),
+            ));
+        }
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeErrors::StackOverflow);
+        }
+
+        let outer = self.environment.enter_function();
+        for (param, argument) in function.params.iter().zip(arguments) {
+            self.environment.define(param, Some(argument));
+        }
+        self.call_depth += 1;
+        let result = self.execute_all(body);
+        self.call_depth -= 1;
+        self.environment.leave_function(outer);
+
+        match result? {
+            Flow::Return(value) => Ok(value),
+            _ => Ok(Value::Unit),
+        }
+    }
+
+    fn assign(&mut self, target: &Expression, value: Value) -> Result<Value, RuntimeErrors> {
+        let ExpressionKind::Symbol(name) = &target.kind else {
+            return Err(RuntimeErrors::InvalidAssignmentTarget);
+        };
+        self.environment.assign(&name.value, value.clone())?;
+        Ok(value)
+    }
+
+    fn integer_literal(
+        value: &str,
+        suffix: &Option<String>,
+        negative: bool,
+    ) -> Result<Value, RuntimeErrors> {
+        Value::integer_literal(value, suffix.as_deref(), negative)
+            .ok_or_else(|| RuntimeErrors::InvalidLiteral(value.to_string()))
+    }
+
+    fn evaluate(&mut self, expression: &Expression) -> Result<Value, RuntimeErrors> {
+        match &expression.kind {
+            ExpressionKind::String(value) => Ok(Value::String(value.clone())),
+            ExpressionKind::Number(value, suffix) => Self::integer_literal(value, suffix, false),
+            ExpressionKind::Float(value) => value
+                .parse::<f64>()
+                .map(Value::Float)
+                .map_err(|_| RuntimeErrors::InvalidLiteral(value.clone())),
+            ExpressionKind::Bool(value) => Ok(Value::Bool(*value)),
+            ExpressionKind::Symbol(name) => self.environment.get(&name.value).cloned(),
+            ExpressionKind::Groupping(expression) => self.evaluate(expression),
+            ExpressionKind::Assignment(target, value) => {
+                let value = self.evaluate(value)?;
+                self.assign(target, value)
+            }
+            // `a += b` behaves like `a = a + b`, the target is read before `b` runs.
+            ExpressionKind::CompoundAssignment(target, operator, value) => {
+                let current = self.evaluate(target)?;
+                let value = current.binary(operator, &self.evaluate(value)?)?;
+                self.assign(target, value)
+            }
+            ExpressionKind::Increment(target, operator, prefix) => {
+                let current = self.evaluate(target)?;
+                let value = self.assign(target, current.unary(operator)?)?;
+                Ok(if *prefix { value } else { current })
+            }
+            // `-128i8` is a literal, its digits alone don't fit the type.
+            ExpressionKind::Unary(operator, expression) => match &expression.kind {
+                ExpressionKind::Number(value, suffix) if operator.kind == TokenKind::Minus => {
+                    Self::integer_literal(value, suffix, true)
+                }
+                _ => self.evaluate(expression)?.unary(&operator.kind),
+            },
+            ExpressionKind::Binary(..) => self.evaluate_binary(expression),
+            ExpressionKind::FunctionCall(callee, arguments) => {
+                let callee = self.evaluate(callee)?;
+                let Value::Function(function) = callee else {
+                    return Err(RuntimeErrors::NotCallable(callee.type_name()));
+                };
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.evaluate(argument))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(&function, arguments)
+            }
+            ExpressionKind::Block(statements, tail) => {
+                self.environment.push_scope();
+                let result = self.evaluate_block(statements, tail.as_deref());
+                self.environment.pop_scope();
+                result
+            }
+            ExpressionKind::If(condition, body, otherwise) => {
+                if self.evaluate(condition)?.as_bool()? {
+                    self.evaluate(body)
+                } else {
+                    self.evaluate(otherwise)
+                }
+            }
+            ExpressionKind::Error => Err(RuntimeErrors::SyntaxError),
+        }
+    }
+
+    /// Evaluates a chain of binary operations like `a + b - c` from the
+    /// innermost one outwards, in a loop so a long chain doesn't recurse once
+    /// per operator.
+    fn evaluate_binary(&mut self, expression: &Expression) -> Result<Value, RuntimeErrors> {
+        let mut operations = Vec::new();
+        let mut left = expression;
+        while let ExpressionKind::Binary(lhs, operator, rhs) = &left.kind {
+            operations.push((operator, rhs));
+            left = lhs;
+        }
+        let mut value = self.evaluate(left)?;
+        for (operator, rhs) in operations.into_iter().rev() {
+            value = match operator {
+                TokenKind::And if !value.as_bool()? => Value::Bool(false),
+                TokenKind::Or if value.as_bool()? => Value::Bool(true),
+                TokenKind::And | TokenKind::Or => Value::Bool(self.evaluate(rhs)?.as_bool()?),
+                _ => value.binary(operator, &self.evaluate(rhs)?)?,
+            };
+        }
+        Ok(value)
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,8 +1,15 @@
 pub mod ast;
+pub mod bytecode;
+pub mod compiler;
+pub mod diagnostic;
 pub mod expression_parser;
+pub mod interpreter;
 pub mod parser;
+pub mod span;
 pub mod statement_parser;
 pub mod symbol_table;
 pub mod token;
 pub mod tokenizer;
 pub mod type_system;
+pub mod value;
+pub mod vm;
@@ -1,8 +1,10 @@
 use std::collections::{HashMap, VecDeque};
 
 use crate::event_script::{
-    ast::{Expression, Statement},
-    token::{Token, TokenKind},
+    ast::{Expression, ExpressionKind, Statement, StatementKind},
+    diagnostic::Diagnostic,
+    span::Span,
+    token::{Associativity, Token, TokenKind},
 };
 
 #[derive(Debug)]
@@ -13,13 +15,86 @@ pub enum ParserErrors {
     NumberIsNotANumber(Token),
     BindingPowerError,
     UnexpectedTokenKind(Token),
+    InvalidAssignmentTarget(Token),
+    NonAssociativeOperator(Token),
+    NestingTooDeep(Token),
+}
+
+/// Deepest nesting of expressions and statements a script may have. The passes
+/// over the syntax tree are recursive, deeper scripts would overflow the stack.
+/// Chains of binary operations like `a + b - c` are walked in loops instead and
+/// don't count, only operands parsed inside another expression or statement do.
+pub const MAX_NESTING_DEPTH: usize = 64;
+
+/// How a token is referred to in messages.
+fn describe(token: &Token) -> String {
+    match token.kind {
+        TokenKind::Eof => "end of file".to_string(),
+        TokenKind::String => format!("`\"{}\"`", token.value),
+        _ => format!("`{}`", token.value),
+    }
+}
+
+impl From<&ParserErrors> for Diagnostic {
+    fn from(error: &ParserErrors) -> Self {
+        match error {
+            ParserErrors::NoFunctionHandler(token) => Diagnostic::error(
+                "E0201",
+                &format!("expected an expression, found {}", describe(token)),
+            )
+            .with_primary(token.span(), &"expected an expression"),
+            ParserErrors::UnexpectedExpressionType(token) => Diagnostic::error(
+                "E0202",
+                &format!("{} cannot follow an expression", describe(token)),
+            )
+            .with_primary(token.span(), &"unexpected token"),
+            ParserErrors::NextTokenNotFound => {
+                Diagnostic::error("E0203", &"unexpected end of input")
+            }
+            ParserErrors::NumberIsNotANumber(token) => Diagnostic::error(
+                "E0204",
+                &format!("invalid number literal {}", describe(token)),
+            )
+            .with_primary(token.span(), &"not a valid number"),
+            ParserErrors::BindingPowerError => {
+                Diagnostic::error("E0205", &"operator has no binding power")
+            }
+            ParserErrors::UnexpectedTokenKind(token) => {
+                Diagnostic::error("E0206", &format!("unexpected {}", describe(token)))
+                    .with_primary(token.span(), &"unexpected token")
+            }
+            ParserErrors::InvalidAssignmentTarget(token) => {
+                Diagnostic::error("E0207", &"invalid left-hand side of assignment")
+                    .with_primary(
+                        token.span(),
+                        &"cannot assign to the expression before this operator",
+                    )
+                    .with_help(&"only variables can be assigned to")
+            }
+            ParserErrors::NonAssociativeOperator(token) => {
+                Diagnostic::error("E0208", &format!("{} cannot be chained", describe(token)))
+                    .with_primary(token.span(), &"second comparison in a row")
+                    .with_help(&"use parentheses to group the comparisons")
+            }
+            ParserErrors::NestingTooDeep(token) => Diagnostic::error(
+                "E0209",
+                &"code is nested too deeply",
+            )
+            .with_primary(token.span(), &"nesting limit reached here")
+            .with_note(&format!(
+                "expressions and statements can be nested at most {MAX_NESTING_DEPTH} levels deep"
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Hash)]
 pub enum LeftDenotationHandlerTypes {
     Default,
     Assignment,
-    // FunctionCall,
+    CompoundAssignment,
+    Increment,
+    FunctionCall,
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Hash)]
@@ -27,6 +102,8 @@ pub enum NullDenotationHandlerTypes {
     Default,
     Groupping,
     Unary,
+    Increment,
+    If,
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Hash)]
@@ -34,6 +111,13 @@ pub enum StatementHandlerTypes {
     Default,
     Variable,
     Block,
+    Function,
+    Return,
+    If,
+    While,
+    For,
+    Break,
+    Continue,
 }
 
 pub struct Parser {
@@ -41,34 +125,63 @@ pub struct Parser {
     left_denotation_lookup: HashMap<TokenKind, LeftDenotationHandlerTypes>,
     null_denotation_lookup: HashMap<TokenKind, NullDenotationHandlerTypes>,
     statement_lookup: HashMap<TokenKind, StatementHandlerTypes>,
+    // Span of the last consumed token, where the node being parsed ends so far.
+    previous: Span,
+    // Syntax errors recovered from so far, in source order.
+    errors: Vec<ParserErrors>,
+    // Nesting of the node being parsed, counted against `MAX_NESTING_DEPTH`.
+    depth: usize,
+    // Current token once every token is consumed.
+    eof: Token,
 }
 
 impl Parser {
     pub(super) fn new(tokens: VecDeque<Token>) -> Self {
+        let eof = tokens
+            .back()
+            .filter(|token| token.kind == TokenKind::Eof)
+            .cloned()
+            .unwrap_or_else(|| Token::new(&TokenKind::Eof, String::new(), 0, 0));
         let mut res = Self {
             tokens,
             left_denotation_lookup: HashMap::new(),
             null_denotation_lookup: HashMap::new(),
             statement_lookup: HashMap::new(),
+            previous: Span::default(),
+            errors: Vec::new(),
+            depth: 0,
+            eof,
         };
 
         res.initialize();
         res
     }
 
-    /// .
+    /// Parses a whole script.
     ///
     /// # Errors
     ///
-    /// This function will return an error if .
+    /// Returns the first syntax error of the script.
     pub fn parse(tokens: Vec<Token>) -> Result<Vec<Statement>, ParserErrors> {
+        let (body, errors) = Self::parse_recovering(tokens);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(body),
+        }
+    }
+
+    /// Parses a whole script, skipping to the next statement after a syntax error.
+    /// Returns every syntax error together with the statements around them, the
+    /// source given up on is left as `Error` nodes.
+    #[must_use]
+    pub fn parse_recovering(tokens: Vec<Token>) -> (Vec<Statement>, Vec<ParserErrors>) {
         let mut parser = Parser::new(tokens.into());
         let mut body: Vec<Statement> = Vec::new();
         while parser.has_tokens() {
-            body.push(parser.parse_statement()?);
+            body.push(parser.parse_statement_recovering());
         }
 
-        Ok(body)
+        (body, parser.errors)
     }
 
     fn has_tokens(&self) -> bool {
@@ -80,7 +193,10 @@ impl Parser {
     /// # Errors
     ///
     /// This function will return an error if .
-    fn try_parse_null_denotaion(&mut self, kind: &TokenKind) -> Result<Expression, ParserErrors> {
+    fn try_parse_null_denotaion(
+        &mut self,
+        kind: &TokenKind,
+    ) -> Result<ExpressionKind, ParserErrors> {
         let handler_type =
             self.null_denotation_lookup
                 .get(kind)
@@ -92,6 +208,8 @@ impl Parser {
             NullDenotationHandlerTypes::Default => self.parse_primary_expression(),
             NullDenotationHandlerTypes::Groupping => self.parse_groupping_expression(),
             NullDenotationHandlerTypes::Unary => self.parse_unary_expression(),
+            NullDenotationHandlerTypes::Increment => self.parse_prefix_increment_expression(),
+            NullDenotationHandlerTypes::If => self.parse_if_expression(),
         }
     }
 
@@ -100,7 +218,7 @@ impl Parser {
         left: Expression,
         new_power: u8,
         kind: &TokenKind,
-    ) -> Result<Expression, ParserErrors> {
+    ) -> Result<ExpressionKind, ParserErrors> {
         let function_type =
             self.left_denotation_lookup
                 .get(kind)
@@ -110,9 +228,14 @@ impl Parser {
 
         match function_type {
             LeftDenotationHandlerTypes::Default => self.parse_binary_expression(left, new_power),
-            LeftDenotationHandlerTypes::Assignment => self.parse_assignment_expression(new_power), // LeftDenotationHandlerTypes::FunctionCall => {
-                                                                                                   //     self.parse_function_call(left, new_power.clone())
-                                                                                                   // }
+            LeftDenotationHandlerTypes::Assignment => {
+                self.parse_assignment_expression(left, new_power)
+            }
+            LeftDenotationHandlerTypes::CompoundAssignment => {
+                self.parse_compound_assignment_expression(left, new_power)
+            }
+            LeftDenotationHandlerTypes::Increment => self.parse_postfix_increment_expression(left),
+            LeftDenotationHandlerTypes::FunctionCall => self.parse_function_call(left),
         }
     }
 
@@ -120,34 +243,220 @@ impl Parser {
         &mut self,
         binding_power: u8,
     ) -> Result<Expression, ParserErrors> {
+        let depth = self.depth;
+        let expression = self.parse_nested_expression(binding_power);
+        self.depth = depth;
+        expression
+    }
+
+    /// Every expression parsed inside another one nests one level deeper.
+    /// Operators applied to `left` don't, they fold it in a loop.
+    fn parse_nested_expression(&mut self, binding_power: u8) -> Result<Expression, ParserErrors> {
+        self.nest()?;
         let mut kind = self.current_token().kind.clone();
 
-        let mut left = self.try_parse_null_denotaion(&kind)?;
+        let mut left = self.parse_spanned(|parser| parser.try_parse_null_denotaion(&kind))?;
 
         while self.current_token().binding_power > binding_power {
             kind = self.current_token().kind.clone();
 
             let new_power = self.current_token().binding_power;
-            left = self.try_parse_left_denotation(left, new_power, &kind)?;
+            let start = left.span;
+            let expression = self.try_parse_left_denotation(left, new_power, &kind)?;
+            left = Expression::new(expression, self.span_from(start));
         }
 
         Ok(left)
     }
 
+    fn nest(&mut self) -> Result<(), ParserErrors> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING_DEPTH {
+            return Err(ParserErrors::NestingTooDeep(self.current_token().clone()));
+        }
+        Ok(())
+    }
+
+    /// Runs a handler and gives the expression it returns the span of the tokens it consumed.
+    pub(super) fn parse_spanned(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<ExpressionKind, ParserErrors>,
+    ) -> Result<Expression, ParserErrors> {
+        let start = self.current_token().span();
+        let expression = parse(self)?;
+        Ok(Expression::new(expression, self.span_from(start)))
+    }
+
+    /// Span from `start` to the end of the last consumed token.
+    pub(super) fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous)
+    }
+
+    /// Binding power the right operand of an infix operator is parsed with.
+    pub(super) fn right_binding_power(kind: &TokenKind, power: u8) -> u8 {
+        match kind.get_associativity() {
+            Associativity::Right => power - 1,
+            Associativity::Left | Associativity::None => power,
+        }
+    }
+
     pub(super) fn parse_statement(&mut self) -> Result<Statement, ParserErrors> {
-        let statement_handler_type = self.statement_lookup.get(&self.current_token().kind);
-        if let Some(statement_handler_type) = statement_handler_type {
-            match *statement_handler_type {
-                StatementHandlerTypes::Default => return self.parse_statement(),
-                StatementHandlerTypes::Variable => return self.parse_variable_statement(),
-                StatementHandlerTypes::Block => return self.parse_block_statement(),
-            };
+        let depth = self.depth;
+        let statement = self.parse_nested_statement();
+        self.depth = depth;
+        statement
+    }
+
+    /// Parses an `if` that starts a statement of a block expression. It counts
+    /// as a statement against the nesting limit.
+    pub(super) fn parse_if_statement_expression(&mut self) -> Result<Expression, ParserErrors> {
+        let depth = self.depth;
+        let expression = self
+            .nest()
+            .and_then(|()| self.parse_spanned(|parser| parser.parse_if(false)));
+        self.depth = depth;
+        expression
+    }
+
+    fn parse_nested_statement(&mut self) -> Result<Statement, ParserErrors> {
+        self.nest()?;
+        let start = self.current_token().span();
+        let statement_handler_type = self
+            .statement_lookup
+            .get(&self.current_token().kind)
+            .cloned();
+        let statement = match statement_handler_type {
+            Some(StatementHandlerTypes::Default) => return self.parse_nested_statement(),
+            Some(StatementHandlerTypes::Variable) => self.parse_variable_statement()?,
+            Some(StatementHandlerTypes::Block) => self.parse_block_statement()?,
+            Some(StatementHandlerTypes::Function) => self.parse_function_statement()?,
+            Some(StatementHandlerTypes::Return) => self.parse_return_statement()?,
+            Some(StatementHandlerTypes::If) => self.parse_if_statement()?,
+            Some(StatementHandlerTypes::While) => self.parse_while_statement()?,
+            Some(StatementHandlerTypes::For) => self.parse_for_statement()?,
+            Some(StatementHandlerTypes::Break | StatementHandlerTypes::Continue) => {
+                self.parse_loop_control_statement()?
+            }
+            None => {
+                let expression = self.parse_expression(0)?;
+                self.expect_semicolon();
+                StatementKind::Expression(expression)
+            }
+        };
+
+        Ok(Statement::new(statement, self.span_from(start)))
+    }
+
+    /// Parses a statement, or records its syntax error and returns an `Error` node.
+    pub(super) fn parse_statement_recovering(&mut self) -> Statement {
+        let start = self.current_token().span();
+        let remaining = self.tokens_left();
+        match self.parse_statement() {
+            Ok(statement) => statement,
+            Err(error) => self.recover_statement(error, start, remaining),
         }
+    }
 
-        let expression = self.parse_expression(0)?;
-        self.expect_token(&TokenKind::SemiColon)?;
+    /// Records a syntax error in the statement that started at `start`, with
+    /// `remaining` tokens left, and skips to the end of that statement.
+    pub(super) fn recover_statement(
+        &mut self,
+        error: ParserErrors,
+        start: Span,
+        remaining: usize,
+    ) -> Statement {
+        self.report(error);
+        // A token no statement can start with, like a stray `}`, is dropped so it isn't retried.
+        if self.tokens_left() == remaining && self.has_tokens() {
+            let _ = self.next_token();
+        }
+        Statement::new(StatementKind::Error, self.span_from(start))
+    }
+
+    /// Records a syntax error in the expression that started at `start` and
+    /// skips to the end of the statement the expression is part of.
+    pub(super) fn recover_expression(&mut self, error: ParserErrors, start: Span) -> Expression {
+        self.report(error);
+        Expression::new(ExpressionKind::Error, self.span_from(start))
+    }
+
+    /// Ends a statement. A missing `;` is recorded instead of returned as the
+    /// statement before it is complete.
+    pub(super) fn expect_semicolon(&mut self) {
+        if let Err(error) = self.expect_token(&TokenKind::SemiColon) {
+            self.report(error);
+        }
+    }
+
+    /// Records a syntax error and skips past it. Nothing after a node nested
+    /// too deep is parsed, the rest of the file is dropped along with the
+    /// errors of the enclosing nodes that no longer find their end.
+    fn report(&mut self, error: ParserErrors) {
+        if matches!(self.errors.last(), Some(ParserErrors::NestingTooDeep(_))) {
+            return;
+        }
+        if matches!(error, ParserErrors::NestingTooDeep(_)) {
+            self.tokens.retain(|token| token.kind == TokenKind::Eof);
+        } else {
+            self.synchronize();
+        }
+        self.errors.push(error);
+    }
+
+    /// Records a syntax error in the header of a function and skips up to the
+    /// end of its body, keeping the braces inside the body balanced.
+    pub(super) fn recover_function_header(&mut self, error: ParserErrors) {
+        use super::token::TokenKind::{CloseCurly, Const, Eof, Fn, For, If, Let, OpenCurly, While};
+        loop {
+            match self.current_token().kind {
+                OpenCurly => break,
+                // Without a body the function ends where the next statement starts.
+                Eof | CloseCurly | Let | Const | Fn | If | While | For => {
+                    return self.report(error);
+                }
+                _ => {
+                    let _ = self.next_token();
+                }
+            }
+        }
+        let mut depth = 0usize;
+        loop {
+            match self.current_token().kind {
+                Eof => break,
+                OpenCurly => depth += 1,
+                CloseCurly => depth -= 1,
+                _ => {}
+            }
+            let _ = self.next_token();
+            if depth == 0 {
+                break;
+            }
+        }
+        self.errors.push(error);
+    }
+
+    /// Skips tokens up to the next place a statement can start: past a `;`, or
+    /// before a `}`, a declaration or a control flow keyword, or the end of the file.
+    /// `return`, `break` and `continue` are skipped along with the rest, after an
+    /// error they more likely belong to a body that was left than start a statement.
+    fn synchronize(&mut self) {
+        use super::token::TokenKind::{CloseCurly, Const, Eof, Fn, For, If, Let, SemiColon, While};
+        loop {
+            match self.current_token().kind {
+                Eof | CloseCurly | Let | Const | Fn | If | While | For => return,
+                SemiColon => {
+                    let _ = self.next_token();
+                    return;
+                }
+                _ => {
+                    let _ = self.next_token();
+                }
+            }
+        }
+    }
 
-        Ok(Statement::Expression(expression))
+    pub(super) fn has_statement_handler(&self, kind: &TokenKind) -> bool {
+        self.statement_lookup.contains_key(kind)
     }
 
     pub(super) fn expect_any_token(
@@ -176,21 +485,30 @@ impl Parser {
         Ok(token)
     }
 
+    pub(super) fn tokens_left(&self) -> usize {
+        self.tokens.len()
+    }
+
     pub(super) fn current_token(&self) -> &Token {
-        self.tokens.front().unwrap()
+        self.tokens.front().unwrap_or(&self.eof)
     }
 
     pub(super) fn next_token(&mut self) -> Result<Token, ParserErrors> {
-        self.tokens
+        let token = self
+            .tokens
             .pop_front()
-            .ok_or(ParserErrors::NextTokenNotFound)
+            .ok_or(ParserErrors::NextTokenNotFound)?;
+        self.previous = token.span();
+        Ok(token)
     }
 
     fn initialize(&mut self) {
         use super::token::TokenKind::{
-            And, Const, DotDot, Equals, Greater, GreaterEquals, Identifier, Less, LessEquals, Let,
-            Minus, Not, NotEquals, Number, OpenCurly, OpenParen, Or, Percent, Plus, Slash, Star,
-            String,
+            Ampersand, And, Break, Caret, Const, Continue, DivideEquals, DotDot, DotDotEquals,
+            Equals, False, Float, Fn, For, Greater, GreaterEquals, Identifier, If, Less,
+            LessEquals, Let, Minus, MinusEquals, MinusMinus, ModEquals, MultiplyEquals, Not,
+            NotEquals, Number, OpenCurly, OpenParen, Or, Percent, Pipe, Plus, PlusEquals, PlusPlus,
+            Return, ShiftLeft, ShiftRight, Slash, Star, String, Tilde, True, While,
         };
 
         let mut add_new = |kind: TokenKind, h_type: LeftDenotationHandlerTypes| {
@@ -199,21 +517,36 @@ impl Parser {
 
         self.null_denotation_lookup
             .insert(Number, NullDenotationHandlerTypes::Default);
+        self.null_denotation_lookup
+            .insert(Float, NullDenotationHandlerTypes::Default);
         self.null_denotation_lookup
             .insert(String, NullDenotationHandlerTypes::Default);
         self.null_denotation_lookup
             .insert(Identifier, NullDenotationHandlerTypes::Default);
+        self.null_denotation_lookup
+            .insert(True, NullDenotationHandlerTypes::Default);
+        self.null_denotation_lookup
+            .insert(False, NullDenotationHandlerTypes::Default);
 
         self.null_denotation_lookup
             .insert(Minus, NullDenotationHandlerTypes::Unary);
         self.null_denotation_lookup
             .insert(Not, NullDenotationHandlerTypes::Unary);
+        self.null_denotation_lookup
+            .insert(Tilde, NullDenotationHandlerTypes::Unary);
+        self.null_denotation_lookup
+            .insert(PlusPlus, NullDenotationHandlerTypes::Increment);
+        self.null_denotation_lookup
+            .insert(MinusMinus, NullDenotationHandlerTypes::Increment);
         self.null_denotation_lookup
             .insert(OpenParen, NullDenotationHandlerTypes::Groupping);
+        self.null_denotation_lookup
+            .insert(If, NullDenotationHandlerTypes::If);
         //Logical
         add_new(And, LeftDenotationHandlerTypes::Default);
         add_new(Or, LeftDenotationHandlerTypes::Default);
         add_new(DotDot, LeftDenotationHandlerTypes::Default);
+        add_new(DotDotEquals, LeftDenotationHandlerTypes::Default);
 
         //Comparison
         add_new(Less, LeftDenotationHandlerTypes::Default);
@@ -223,6 +556,13 @@ impl Parser {
         add_new(Equals, LeftDenotationHandlerTypes::Default);
         add_new(NotEquals, LeftDenotationHandlerTypes::Default);
 
+        //Bitwise
+        add_new(Pipe, LeftDenotationHandlerTypes::Default);
+        add_new(Caret, LeftDenotationHandlerTypes::Default);
+        add_new(Ampersand, LeftDenotationHandlerTypes::Default);
+        add_new(ShiftLeft, LeftDenotationHandlerTypes::Default);
+        add_new(ShiftRight, LeftDenotationHandlerTypes::Default);
+
         //Math
         add_new(Plus, LeftDenotationHandlerTypes::Default);
         add_new(Minus, LeftDenotationHandlerTypes::Default);
@@ -231,13 +571,23 @@ impl Parser {
         add_new(Slash, LeftDenotationHandlerTypes::Default);
         add_new(Percent, LeftDenotationHandlerTypes::Default);
 
-        // add_new(OpenParen, LeftDenotationHandlerTypes::FunctionCall);
+        add_new(OpenParen, LeftDenotationHandlerTypes::FunctionCall);
+        add_new(PlusPlus, LeftDenotationHandlerTypes::Increment);
+        add_new(MinusMinus, LeftDenotationHandlerTypes::Increment);
 
         //
         add_new(
             TokenKind::Assignment,
             LeftDenotationHandlerTypes::Assignment,
         );
+        add_new(PlusEquals, LeftDenotationHandlerTypes::CompoundAssignment);
+        add_new(MinusEquals, LeftDenotationHandlerTypes::CompoundAssignment);
+        add_new(
+            MultiplyEquals,
+            LeftDenotationHandlerTypes::CompoundAssignment,
+        );
+        add_new(DivideEquals, LeftDenotationHandlerTypes::CompoundAssignment);
+        add_new(ModEquals, LeftDenotationHandlerTypes::CompoundAssignment);
 
         //Statements
         self.statement_lookup
@@ -246,5 +596,18 @@ impl Parser {
             .insert(Const, StatementHandlerTypes::Variable);
         self.statement_lookup
             .insert(OpenCurly, StatementHandlerTypes::Block);
+        self.statement_lookup
+            .insert(Fn, StatementHandlerTypes::Function);
+        self.statement_lookup
+            .insert(Return, StatementHandlerTypes::Return);
+        self.statement_lookup.insert(If, StatementHandlerTypes::If);
+        self.statement_lookup
+            .insert(While, StatementHandlerTypes::While);
+        self.statement_lookup
+            .insert(For, StatementHandlerTypes::For);
+        self.statement_lookup
+            .insert(Break, StatementHandlerTypes::Break);
+        self.statement_lookup
+            .insert(Continue, StatementHandlerTypes::Continue);
     }
 }
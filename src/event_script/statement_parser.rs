@@ -1,21 +1,131 @@
 use super::parser::{Parser, ParserErrors};
 use super::token::TokenKind;
-use crate::event_script::ast::Statement;
+use crate::event_script::ast::{Statement, StatementKind};
+
+/// Name, parameters with their type names and return type name of a function.
+type FunctionHeader = (String, Vec<(String, String)>, Option<String>);
 
 impl Parser {
-    pub(super) fn parse_block_statement(&mut self) -> Result<Statement, ParserErrors> {
+    pub(super) fn parse_block_statement(&mut self) -> Result<StatementKind, ParserErrors> {
+        Ok(StatementKind::Block(self.parse_block_body()?))
+    }
+
+    pub(super) fn parse_block_body(&mut self) -> Result<Vec<Statement>, ParserErrors> {
         self.expect_token(&TokenKind::OpenCurly)?;
         let mut statements = Vec::new();
-        while self.current_token().kind != TokenKind::CloseCurly {
-            statements.push(self.parse_statement()?);
+        while !matches!(
+            self.current_token().kind,
+            TokenKind::CloseCurly | TokenKind::Eof
+        ) {
+            statements.push(self.parse_statement_recovering());
         }
 
         self.expect_token(&TokenKind::CloseCurly)?;
 
-        Ok(Statement::Block(statements))
+        Ok(statements)
+    }
+
+    pub(super) fn parse_function_statement(&mut self) -> Result<StatementKind, ParserErrors> {
+        let (name, params, return_type) = match self.parse_function_header() {
+            Ok(header) => header,
+            // The body of a function with a broken header is skipped whole, its
+            // statements would only be reported again outside of the function.
+            Err(error) => {
+                self.recover_function_header(error);
+                return Ok(StatementKind::Error);
+            }
+        };
+        let body = self.parse_block_body()?;
+
+        Ok(StatementKind::Function(name, params, return_type, body))
+    }
+
+    fn parse_function_header(&mut self) -> Result<FunctionHeader, ParserErrors> {
+        self.expect_token(&TokenKind::Fn)?;
+        let name = self.expect_token(&TokenKind::Identifier)?.value;
+
+        self.expect_token(&TokenKind::OpenParen)?;
+        let mut params = Vec::new();
+        while self.current_token().kind != TokenKind::CloseParen {
+            if !params.is_empty() {
+                self.expect_token(&TokenKind::Comma)?;
+            }
+            let param_name = self.expect_token(&TokenKind::Identifier)?.value;
+            self.expect_token(&TokenKind::Colon)?;
+            let param_type = self.expect_token(&TokenKind::Identifier)?.value;
+            params.push((param_name, param_type));
+        }
+        self.expect_token(&TokenKind::CloseParen)?;
+
+        let mut return_type = None;
+        if self.current_token().kind == TokenKind::Colon {
+            self.next_token()?;
+            return_type = Some(self.expect_token(&TokenKind::Identifier)?.value);
+        }
+
+        Ok((name, params, return_type))
+    }
+
+    pub(super) fn parse_return_statement(&mut self) -> Result<StatementKind, ParserErrors> {
+        self.expect_token(&TokenKind::Return)?;
+        let mut value = None;
+        if self.current_token().kind != TokenKind::SemiColon {
+            value = Some(self.parse_expression(0)?);
+        }
+        self.expect_semicolon();
+
+        Ok(StatementKind::Return(value))
+    }
+
+    pub(super) fn parse_if_statement(&mut self) -> Result<StatementKind, ParserErrors> {
+        self.expect_token(&TokenKind::If)?;
+        let condition = self.parse_expression(0)?;
+        let body = self.parse_block_body()?;
+
+        let mut otherwise = None;
+        if self.current_token().kind == TokenKind::Else {
+            self.next_token()?;
+            let start = self.current_token().span();
+            let branch = if self.current_token().kind == TokenKind::If {
+                self.parse_if_statement()?
+            } else {
+                self.parse_block_statement()?
+            };
+            otherwise = Some(Box::new(Statement::new(branch, self.span_from(start))));
+        }
+
+        Ok(StatementKind::If(condition, body, otherwise))
+    }
+
+    pub(super) fn parse_while_statement(&mut self) -> Result<StatementKind, ParserErrors> {
+        self.expect_token(&TokenKind::While)?;
+        let condition = self.parse_expression(0)?;
+        let body = self.parse_block_body()?;
+
+        Ok(StatementKind::While(condition, body))
+    }
+
+    pub(super) fn parse_for_statement(&mut self) -> Result<StatementKind, ParserErrors> {
+        self.expect_token(&TokenKind::For)?;
+        let name = self.expect_token(&TokenKind::Identifier)?.value;
+        self.expect_token(&TokenKind::In)?;
+        let iterable = self.parse_expression(0)?;
+        let body = self.parse_block_body()?;
+
+        Ok(StatementKind::For(name, iterable, body))
+    }
+
+    pub(super) fn parse_loop_control_statement(&mut self) -> Result<StatementKind, ParserErrors> {
+        let token = self.expect_any_token(&vec![&TokenKind::Break, &TokenKind::Continue])?;
+        self.expect_semicolon();
+
+        match token.kind {
+            TokenKind::Break => Ok(StatementKind::Break),
+            _ => Ok(StatementKind::Continue),
+        }
     }
 
-    pub(super) fn parse_variable_statement(&mut self) -> Result<Statement, ParserErrors> {
+    pub(super) fn parse_variable_statement(&mut self) -> Result<StatementKind, ParserErrors> {
         let let_token = self.next_token()?;
         let is_const = match let_token.kind {
             TokenKind::Const => true,
@@ -43,7 +153,7 @@ impl Parser {
         //let after_name = self.next_token()?;
         let has_explicit_type = match self.current_token().kind {
             TokenKind::Colon => true,
-            TokenKind::Assignment => false,
+            TokenKind::Assignment | TokenKind::SemiColon => false,
             _ => {
                 return Err(ParserErrors::UnexpectedTokenKind(
                     self.current_token().clone(),
@@ -64,9 +174,17 @@ impl Parser {
         let token = self.expect_any_token(&vec![&TokenKind::Assignment, &TokenKind::SemiColon])?;
         let mut expr = None;
         match token.kind {
+            // The variable stays declared when its initializer is broken, so
+            // its uses don't report errors of their own.
             TokenKind::Assignment => {
-                expr = Some(self.parse_expression(0)?);
-                self.expect_token(&TokenKind::SemiColon)?;
+                let start = self.current_token().span();
+                expr = Some(match self.parse_expression(0) {
+                    Ok(expression) => {
+                        self.expect_semicolon();
+                        expression
+                    }
+                    Err(error) => self.recover_expression(error, start),
+                });
             }
             TokenKind::SemiColon => {}
             _ => {
@@ -74,8 +192,8 @@ impl Parser {
             }
         }
 
-        Ok(Statement::Variable(
-            name_token.value,
+        Ok(StatementKind::Variable(
+            name_token,
             is_const,
             is_mutable,
             explicit_type_val,
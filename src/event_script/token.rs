@@ -1,5 +1,19 @@
 use std::fmt::Display;
 
+use crate::event_script::span::{FileId, Span};
+
+/// Binding power of prefix operators, tighter than every binary operator.
+pub const PREFIX_BINDING_POWER: u8 = 13;
+
+/// How a chain of operators with the same binding power groups.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Associativity {
+    Left,
+    Right,
+    /// Chaining is a syntax error, as in `a < b < c`.
+    None,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub enum TokenKind {
     Eof,
@@ -7,6 +21,7 @@ pub enum TokenKind {
     True,
     False,
     Number,
+    Float,
     String,
     Identifier,
 
@@ -35,8 +50,16 @@ pub enum TokenKind {
     Or,
     And,
 
+    // Bitwise, `Pipe` doubles as the bitwise or
+    Ampersand,
+    Caret,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
+
     // Symbols
     DotDot,
+    DotDotEquals,
     Dot,
     SemiColon,
     DoubleColon,
@@ -100,6 +123,8 @@ impl TokenKind {
             "return" => Some(TokenKind::Return),
             "continue" => Some(TokenKind::Continue),
             "break" => Some(TokenKind::Break),
+            "true" => Some(TokenKind::True),
+            "false" => Some(TokenKind::False),
             _ => None,
         }
     }
@@ -107,28 +132,109 @@ impl TokenKind {
     #[must_use]
     pub fn get_binding_power(&self) -> u8 {
         use TokenKind::{
-            And, Assignment, DotDot, Equals, Greater, GreaterEquals, Less, LessEquals, Minus,
-            NotEquals, OpenParen, Or, Percent, Plus, Slash, Star,
+            Ampersand, And, Assignment, Caret, DivideEquals, DotDot, DotDotEquals, Equals, Greater,
+            GreaterEquals, Less, LessEquals, Minus, MinusEquals, MinusMinus, ModEquals,
+            MultiplyEquals, NotEquals, OpenParen, Or, Percent, Pipe, Plus, PlusEquals, PlusPlus,
+            ShiftLeft, ShiftRight, Slash, Star,
         };
         match self {
-            Assignment => 2,
-            And | Or | DotDot => 3,
-            Less | LessEquals | Greater | GreaterEquals | Equals | NotEquals => 4,
-            Plus | Minus => 5,
-            Slash | Star | Percent => 6,
-            OpenParen => 8,
+            Assignment | PlusEquals | MinusEquals | MultiplyEquals | DivideEquals | ModEquals => 2,
+            DotDot | DotDotEquals => 3,
+            Or => 4,
+            And => 5,
+            Less | LessEquals | Greater | GreaterEquals | Equals | NotEquals => 6,
+            Pipe => 7,
+            Caret => 8,
+            Ampersand => 9,
+            ShiftLeft | ShiftRight => 10,
+            Plus | Minus => 11,
+            Slash | Star | Percent => 12,
+            // Prefix operators bind at `PREFIX_BINDING_POWER`.
+            OpenParen => 14,
+            PlusPlus | MinusMinus => 15,
             _ => 0,
         }
     }
+
+    #[must_use]
+    pub fn get_associativity(&self) -> Associativity {
+        use TokenKind::{
+            Assignment, DivideEquals, DotDot, DotDotEquals, Equals, Greater, GreaterEquals, Less,
+            LessEquals, MinusEquals, ModEquals, MultiplyEquals, NotEquals, PlusEquals,
+        };
+        match self {
+            Assignment | PlusEquals | MinusEquals | MultiplyEquals | DivideEquals | ModEquals => {
+                Associativity::Right
+            }
+            DotDot | DotDotEquals | Less | LessEquals | Greater | GreaterEquals | Equals
+            | NotEquals => Associativity::None,
+            _ => Associativity::Left,
+        }
+    }
+
+    /// Source text of an operator, used to name it in diagnostics.
+    #[must_use]
+    pub fn operator_symbol(&self) -> Option<&'static str> {
+        use TokenKind::{
+            Ampersand, And, Assignment, Caret, DivideEquals, DotDot, DotDotEquals, Equals, Greater,
+            GreaterEquals, Less, LessEquals, Minus, MinusEquals, MinusMinus, ModEquals,
+            MultiplyEquals, Not, NotEquals, Or, Percent, Pipe, Plus, PlusEquals, PlusPlus,
+            ShiftLeft, ShiftRight, Slash, Star, Tilde,
+        };
+        let symbol = match self {
+            Assignment => "=",
+            PlusEquals => "+=",
+            MinusEquals => "-=",
+            MultiplyEquals => "*=",
+            DivideEquals => "/=",
+            ModEquals => "%=",
+            DotDot => "..",
+            DotDotEquals => "..=",
+            Or => "||",
+            And => "&&",
+            Less => "<",
+            LessEquals => "<=",
+            Greater => ">",
+            GreaterEquals => ">=",
+            Equals => "==",
+            NotEquals => "!=",
+            Pipe => "|",
+            Caret => "^",
+            Ampersand => "&",
+            ShiftLeft => "<<",
+            ShiftRight => ">>",
+            Plus => "+",
+            Minus => "-",
+            Star => "*",
+            Slash => "/",
+            Percent => "%",
+            Not => "!",
+            Tilde => "~",
+            PlusPlus => "++",
+            MinusMinus => "--",
+            _ => return None,
+        };
+        Some(symbol)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Token {
     pub kind: TokenKind,
     pub value: String,
+    /// 1-based line the token starts on.
     pub line: usize,
+    /// 1-based column the token starts at, counted in chars.
     pub pos: usize,
+    /// File the token was read from.
+    pub file: FileId,
+    /// Byte offset of the token in the source.
+    pub start: usize,
+    /// Byte offset just past the end of the token.
+    pub end: usize,
     pub binding_power: u8,
+    /// Type suffix of an integer literal, e.g. `u8` in `10u8`.
+    pub suffix: Option<String>,
 }
 
 impl Token {
@@ -139,16 +245,26 @@ impl Token {
             value,
             line,
             pos,
+            file: 0,
+            start: 0,
+            end: 0,
             binding_power: kind.get_binding_power(),
+            suffix: None,
         }
     }
+
+    #[must_use]
+    pub fn span(&self) -> Span {
+        Span::new(self.file, self.start, self.end)
+    }
 }
 
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.kind {
-            TokenKind::Identifier | TokenKind::String | TokenKind::Number => {
-                write!(f, "{:?}({})", self.kind, self.value)
+            TokenKind::Identifier | TokenKind::String | TokenKind::Number | TokenKind::Float => {
+                let suffix = self.suffix.as_deref().unwrap_or_default();
+                write!(f, "{:?}({}{suffix})", self.kind, self.value)
             }
             _ => write!(f, "{:?}", self.kind),
         }
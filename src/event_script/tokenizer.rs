@@ -1,33 +1,29 @@
 use std::fmt::Display;
-use std::ops::Deref;
 
-use crate::event_script::token::{Token, TokenKind};
-use lazy_regex::{Regex, regex};
+use crate::event_script::{
+    diagnostic::Diagnostic,
+    span::{FileId, Span},
+    token::{Token, TokenKind},
+};
 
-pub struct Lexer {
+/// Single pass scanner over the source, each token is recognized by looking at
+/// its first few bytes and always takes the longest operator it can.
+pub struct Lexer<'a> {
     tokens: Vec<Token>,
-    input: String,
+    input: &'a str,
+    file: FileId,
     pos: usize,
-    patterns: Vec<RegexPattern>,
-}
-
-pub type HandlerType = dyn Fn(&Regex, &str, usize, usize) -> (usize, Option<Token>);
-
-pub struct RegexPattern {
-    regex: regex::Regex,
-    handler: Box<HandlerType>,
-}
-
-impl RegexPattern {
-    #[must_use]
-    pub fn new(regex: Regex, handler: Box<HandlerType>) -> Self {
-        RegexPattern { regex, handler }
-    }
+    // Line and column of the byte offset `located`, which only moves forward.
+    line: usize,
+    column: usize,
+    located: usize,
 }
 
 #[derive(Debug)]
 pub enum TokenizerError {
-    InvalidToken(String),
+    InvalidToken(char, Span),
+    UnterminatedString(Span),
+    InvalidSuffix(String, Span),
 }
 
 impl Display for TokenizerError {
@@ -38,212 +34,318 @@ impl Display for TokenizerError {
 
 impl std::error::Error for TokenizerError {}
 
-fn default_handler(kind: TokenKind, value: &'static str) -> Box<HandlerType> {
-    let res = move |_: &Regex, _: &str, line: usize, pos: usize| -> (usize, Option<Token>) {
-        (
-            value.len(),
-            Some(Token::new(&kind, value.to_string(), line, pos)),
-        )
-    };
-    Box::new(res)
-}
-
-fn skip_handler(regex: &Regex, remainder: &str, _: usize, _: usize) -> (usize, Option<Token>) {
-    if let Some(pat) = regex.find(remainder) {
-        return (pat.end(), None);
+impl From<&TokenizerError> for Diagnostic {
+    fn from(error: &TokenizerError) -> Self {
+        match error {
+            TokenizerError::InvalidToken(character, span) => {
+                Diagnostic::error("E0101", &format!("unexpected character `{character}`"))
+                    .with_primary(*span, &"no token starts with this character")
+            }
+            TokenizerError::UnterminatedString(span) => {
+                Diagnostic::error("E0102", &"unterminated string literal")
+                    .with_primary(*span, &"string starts here")
+                    .with_help(&"add a closing `\"`")
+            }
+            TokenizerError::InvalidSuffix(suffix, span) => {
+                Diagnostic::error("E0103", &format!("invalid suffix `{suffix}` for number literal"))
+                    .with_primary(*span, &"invalid suffix")
+                    .with_help(
+                        &"integer literals can end in `i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32` or `u64`",
+                    )
+            }
+        }
     }
-    (0, None)
 }
 
-fn string_handler(
-    regex: &Regex,
-    remainder: &str,
-    line: usize,
-    pos: usize,
-) -> (usize, Option<Token>) {
-    if let Some(pat) = regex.find(remainder) {
-        let str_value = pat.as_str().trim_matches('\"');
-        return (
-            str_value.len() + 2,
-            Some(Token::new(
-                &TokenKind::String,
-                str_value.to_string(),
-                line,
-                pos,
-            )),
-        );
-    }
-    (0, None)
-}
+const INTEGER_SUFFIXES: [&str; 8] = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"];
 
-fn number_handler(
-    regex: &Regex,
-    remainder: &str,
-    line: usize,
-    pos: usize,
-) -> (usize, Option<Token>) {
-    if let Some(pat) = regex.find(remainder) {
-        let num_value = pat.as_str();
-        return (
-            num_value.len(),
-            Some(Token::new(
-                &TokenKind::Number,
-                String::from(num_value),
-                line,
-                pos,
-            )),
-        );
-    }
-    (0, None)
+/// Strips digit separators and converts `0x`, `0o` and `0b` literals to decimal.
+/// Literals too large to convert are kept verbatim so the type checker can report them.
+fn normalize_integer(literal: &str) -> String {
+    let digits = literal.replace('_', "");
+    let radix = match digits.get(..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => return digits,
+    };
+    u128::from_str_radix(&digits[2..], radix).map_or(digits, |value| value.to_string())
 }
 
-fn symbol_handler(
-    regex: &Regex,
-    remainder: &str,
-    line: usize,
-    pos: usize,
-) -> (usize, Option<Token>) {
-    if let Some(pat) = regex.find(remainder) {
-        let value = pat.as_str();
-        if let Some(keyword) = TokenKind::is_keyword(value) {
-            return (
-                value.len(),
-                Some(Token::new(&keyword, value.to_string(), line, pos)),
-            );
-        }
-        return (
-            value.len(),
-            Some(Token::new(
-                &TokenKind::Identifier,
-                String::from(value),
-                line,
-                pos,
-            )),
-        );
-    }
-    (0, None)
-}
+/// Operator or punctuation at the start of `remainder`, with its length.
+fn operator(remainder: &[u8]) -> Option<(TokenKind, usize)> {
+    use crate::event_script::token::TokenKind::{
+        Ampersand, And, Assignment, Caret, CloseBracket, CloseCurly, CloseParen, Colon, Comma,
+        DivideEquals, Dot, DotDot, DotDotEquals, DoubleColon, Equals, Greater, GreaterEquals, Less,
+        LessEquals, Minus, MinusEquals, MinusMinus, ModEquals, MultiplyEquals, Not, NotEquals,
+        OpenBracket, OpenCurly, OpenParen, Or, Percent, Pipe, Plus, PlusEquals, PlusPlus, Question,
+        SemiColon, ShiftLeft, ShiftRight, Slash, Star, Tilde,
+    };
 
-impl Lexer {
-    fn new(source: String) -> Self {
-        use crate::event_script::token::TokenKind::{
-            And, Assignment, CloseBracket, CloseCurly, CloseParen, Colon, Comma, DivideEquals, Dot,
-            DotDot, DoubleColon, Equals, Greater, GreaterEquals, Less, LessEquals, Minus,
-            MinusEquals, MinusMinus, ModEquals, MultiplyEquals, Not, NotEquals, OpenBracket,
-            OpenCurly, OpenParen, Or, Percent, Pipe, Plus, PlusEquals, PlusPlus, Question,
-            SemiColon, Slash, Star,
-        };
+    let operator = match remainder {
+        //Grouping
+        [b'[', ..] => (OpenBracket, 1),
+        [b']', ..] => (CloseBracket, 1),
+        [b'{', ..] => (OpenCurly, 1),
+        [b'}', ..] => (CloseCurly, 1),
+        [b'(', ..] => (OpenParen, 1),
+        [b')', ..] => (CloseParen, 1),
+        //Equivilance
+        [b'=', b'=', ..] => (Equals, 2),
+        [b'=', ..] => (Assignment, 1),
+        [b'!', b'=', ..] => (NotEquals, 2),
+        [b'!', ..] => (Not, 1),
+        //Conditional & Bitwise
+        [b'<', b'<', ..] => (ShiftLeft, 2),
+        [b'<', b'=', ..] => (LessEquals, 2),
+        [b'<', ..] => (Less, 1),
+        [b'>', b'>', ..] => (ShiftRight, 2),
+        [b'>', b'=', ..] => (GreaterEquals, 2),
+        [b'>', ..] => (Greater, 1),
+        //Logical & Bitwise
+        [b'|', b'|', ..] => (Or, 2),
+        [b'|', ..] => (Pipe, 1),
+        [b'&', b'&', ..] => (And, 2),
+        [b'&', ..] => (Ampersand, 1),
+        [b'^', ..] => (Caret, 1),
+        [b'~', ..] => (Tilde, 1),
+        //Symbols
+        [b'.', b'.', b'=', ..] => (DotDotEquals, 3),
+        [b'.', b'.', ..] => (DotDot, 2),
+        [b'.', ..] => (Dot, 1),
+        [b';', ..] => (SemiColon, 1),
+        [b':', b':', ..] => (DoubleColon, 2),
+        [b':', ..] => (Colon, 1),
+        [b'?', ..] => (Question, 1),
+        [b',', ..] => (Comma, 1),
+        //Shorthand & Maths
+        [b'+', b'+', ..] => (PlusPlus, 2),
+        [b'+', b'=', ..] => (PlusEquals, 2),
+        [b'+', ..] => (Plus, 1),
+        [b'-', b'-', ..] => (MinusMinus, 2),
+        [b'-', b'=', ..] => (MinusEquals, 2),
+        [b'-', ..] => (Minus, 1),
+        [b'/', b'=', ..] => (DivideEquals, 2),
+        [b'/', ..] => (Slash, 1),
+        [b'*', b'=', ..] => (MultiplyEquals, 2),
+        [b'*', ..] => (Star, 1),
+        [b'%', b'=', ..] => (ModEquals, 2),
+        [b'%', ..] => (Percent, 1),
+        _ => return None,
+    };
+    Some(operator)
+}
 
-        #[rustfmt::skip]
-        let patterns = vec![
-            //Grouping
-            RegexPattern::new(regex!(r#"\|"#).deref().to_owned(), default_handler(Pipe, "|")),
-            RegexPattern::new(regex!(r#"\["#).deref().to_owned(), default_handler(OpenBracket, "[")),
-            RegexPattern::new(regex!(r#"\]"#).deref().to_owned(), default_handler(CloseBracket, "]")),
-            RegexPattern::new(regex!(r#"\{"#).deref().to_owned(), default_handler(OpenCurly, "{")),
-            RegexPattern::new(regex!(r#"\}"#).deref().to_owned(), default_handler(CloseCurly, "}")),
-            RegexPattern::new(regex!(r#"\("#).deref().to_owned(), default_handler(OpenParen, "(")),
-            RegexPattern::new(regex!(r#"\)"#).deref().to_owned(), default_handler(CloseParen, ")")),
-            //Equivilance
-            RegexPattern::new(regex!(r#"=="#).deref().to_owned(), default_handler(Equals, "==")),
-            RegexPattern::new(regex!(r#"!="#).deref().to_owned(), default_handler(NotEquals, "!=")),
-            RegexPattern::new(regex!(r#"="#).deref().to_owned(), default_handler(Assignment, "=")),
-            RegexPattern::new(regex!(r#"!"#).deref().to_owned(), default_handler(Not, "!")),
-            //Conditional
-            RegexPattern::new(regex!(r#"<="#).deref().to_owned(), default_handler(LessEquals, "<=")),
-            RegexPattern::new(regex!(r#"<"#).deref().to_owned(), default_handler(Less, "<")),
-            RegexPattern::new(regex!(r#">="#).deref().to_owned(), default_handler(GreaterEquals, ">=")),
-            RegexPattern::new(regex!(r#">"#).deref().to_owned(), default_handler(Greater, ">")),
-            //Logical
-            RegexPattern::new(regex!(r#"\|\|"#).deref().to_owned(), default_handler(Or, "||")),
-            RegexPattern::new(regex!(r#"&&"#).deref().to_owned(), default_handler(And, "&&")),
-            //Symbols
-            RegexPattern::new(regex!(r#"\.\."#).deref().to_owned(), default_handler(DotDot, "..")),
-            RegexPattern::new(regex!(r#"\."#).deref().to_owned(), default_handler(Dot, ".")),
-            RegexPattern::new(regex!(r#";"#).deref().to_owned(), default_handler(SemiColon, ";")),
-            RegexPattern::new(regex!(r#"::"#).deref().to_owned(), default_handler(DoubleColon, "::")),
-            RegexPattern::new(regex!(r#":"#).deref().to_owned(), default_handler(Colon, ":")),
-            RegexPattern::new(regex!(r#"\?"#).deref().to_owned(), default_handler(Question, "?")),
-            RegexPattern::new(regex!(r#","#).deref().to_owned(), default_handler(Comma, ",")),
-            //Shorthand
-            RegexPattern::new(regex!(r#"\+\+"#).deref().to_owned(), default_handler(PlusPlus, "++")),
-            RegexPattern::new(regex!(r#"--"#).deref().to_owned(), default_handler(MinusMinus, "--")),
-            RegexPattern::new(regex!(r#"\+="#).deref().to_owned(), default_handler(PlusEquals, "+=")),
-            RegexPattern::new(regex!(r#"-="#).deref().to_owned(), default_handler(MinusEquals, "-=")),
-            RegexPattern::new(regex!(r#"/="#).deref().to_owned(), default_handler(DivideEquals, "/=")),
-            RegexPattern::new(regex!(r#"\*="#).deref().to_owned(), default_handler(MultiplyEquals, "*=")),
-            RegexPattern::new(regex!(r#"\%="#).deref().to_owned(), default_handler(ModEquals, "%=")),
-            //Maths
-            RegexPattern::new(regex!(r#"\+"#).deref().to_owned(), default_handler(Plus, "+")),
-            RegexPattern::new(regex!(r#"-"#).deref().to_owned(), default_handler(Minus, "-")),
-            RegexPattern::new(regex!(r#"/"#).deref().to_owned(), default_handler(Slash, "/")),
-            RegexPattern::new(regex!(r#"\*"#).deref().to_owned(), default_handler(Star, "*")),
-            RegexPattern::new(regex!(r#"%"#).deref().to_owned(), default_handler(Percent, "%")),
-            //Special cases
-            RegexPattern::new(regex!(r#"\s+"#).deref().to_owned(), Box::new(skip_handler)),
-            RegexPattern::new(regex!(r#"\/\/.*"#).deref().to_owned(), Box::new(skip_handler)),
-            RegexPattern::new(regex!(r#""[^"]*""#).deref().to_owned(), Box::new(string_handler)),
-            RegexPattern::new(regex!(r#"[0-9]+(\.[0-9]+)?"#).deref().to_owned(), Box::new(number_handler)),
-            RegexPattern::new(regex!(r#"[a-zA-Z_][a-zA-Z0-9_!]*"#).deref().to_owned(), Box::new(symbol_handler)),
-        ];
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str, file: FileId) -> Self {
         Self {
             tokens: vec![],
-            input: source,
+            input,
+            file,
             pos: 0,
-            patterns,
+            line: 1,
+            column: 1,
+            located: 0,
         }
     }
 
-    fn remainder(&self) -> String {
-        self.input[self.pos..].to_string()
+    fn remainder(&self) -> &'a str {
+        &self.input[self.pos..]
     }
 
     fn at_eof(&self) -> bool {
         self.pos >= self.input.len()
     }
 
-    /// .
+    /// Advances past every leading byte matching `predicate` and returns the
+    /// number of bytes skipped.
+    fn skip_while(&mut self, predicate: impl Fn(u8) -> bool) -> usize {
+        let skipped = self
+            .remainder()
+            .bytes()
+            .position(|byte| !predicate(byte))
+            .unwrap_or(self.remainder().len());
+        self.pos += skipped;
+        skipped
+    }
+
+    /// Line and column of a byte offset at or after the last located one.
+    fn locate(&mut self, offset: usize) -> (usize, usize) {
+        for character in self.input[self.located..offset].chars() {
+            if character == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.located = offset;
+        (self.line, self.column)
+    }
+
+    /// Adds a token spanning from `start` to the current position.
+    fn push(&mut self, kind: &TokenKind, value: String, start: usize) -> &mut Token {
+        let (line, column) = self.locate(start);
+        let mut token = Token::new(kind, value, line, column);
+        token.file = self.file;
+        token.start = start;
+        token.end = self.pos;
+        self.tokens.push(token);
+        self.tokens.last_mut().expect("a token was just pushed")
+    }
+
+    fn invalid_token(&self) -> TokenizerError {
+        let character = self.remainder().chars().next().unwrap_or_default();
+        let span = Span::new(self.file, self.pos, self.pos + character.len_utf8());
+        TokenizerError::InvalidToken(character, span)
+    }
+
+    /// Splits the source into tokens, taking the longest token possible at each position.
     ///
     /// # Errors
     ///
-    /// This function will return an error if .
+    /// Returns an error if no token starts at some position of the source, a
+    /// string is never closed or a number ends in an invalid suffix.
     pub fn tokenize(source: String) -> Result<Vec<Token>, TokenizerError> {
-        let mut lexer = Lexer::new(source);
+        Self::tokenize_file(source, 0)
+    }
+
+    /// Same as [`Lexer::tokenize`], the tokens' spans point into `file`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no token starts at some position of the source, a
+    /// string is never closed or a number ends in an invalid suffix.
+    pub fn tokenize_file(source: String, file: FileId) -> Result<Vec<Token>, TokenizerError> {
+        let mut lexer = Lexer::new(&source, file);
 
         while !lexer.at_eof() {
-            let mut matched = false;
-
-            let remainder = lexer.remainder().clone();
-            let mut advance = 0;
-            let mut token = None;
-            for pattern in &mut lexer.patterns {
-                if let Some(location) = pattern.regex.find(&remainder)
-                    && location.start() == 0
-                {
-                    (advance, token) = (pattern.handler)(&pattern.regex, &remainder, lexer.pos, 0);
-                    matched = true;
-                    break;
+            let remainder = lexer.remainder();
+            let Some(current) = remainder.chars().next() else {
+                break;
+            };
+            match current {
+                current if current.is_whitespace() => lexer.pos += current.len_utf8(),
+                '/' if remainder.starts_with("//") => {
+                    lexer.skip_while(|byte| byte != b'\n');
+                }
+                '"' => lexer.scan_string()?,
+                '0'..='9' => lexer.scan_number()?,
+                'a'..='z' | 'A'..='Z' | '_' => lexer.scan_symbol(),
+                _ => {
+                    let (kind, length) =
+                        operator(remainder.as_bytes()).ok_or_else(|| lexer.invalid_token())?;
+                    let start = lexer.pos;
+                    lexer.pos += length;
+                    lexer.push(&kind, remainder[..length].to_string(), start);
                 }
             }
+        }
+
+        let pos = lexer.pos;
+        lexer.push(&TokenKind::Eof, String::new(), pos);
 
-            if !matched {
-                let pos = lexer.pos;
-                return Err(TokenizerError::InvalidToken(format!(
-                    "pos: {pos} source_len: {}",
-                    lexer.input.len()
-                )));
+        Ok(lexer.tokens)
+    }
+
+    fn scan_string(&mut self) -> Result<(), TokenizerError> {
+        let start = self.pos;
+        let Some(length) = self.remainder()[1..].find('"') else {
+            let span = Span::new(self.file, start, start + 1);
+            return Err(TokenizerError::UnterminatedString(span));
+        };
+        let value = self.remainder()[1..=length].to_string();
+        self.pos += length + 2;
+        self.push(&TokenKind::String, value, start);
+        Ok(())
+    }
+
+    fn scan_number(&mut self) -> Result<(), TokenizerError> {
+        let start = self.pos;
+        let radix_digit: Option<fn(u8) -> bool> = match self.remainder().as_bytes() {
+            [b'0', b'x', ..] => Some(|byte| byte.is_ascii_hexdigit()),
+            [b'0', b'o', ..] => Some(|byte| matches!(byte, b'0'..=b'7')),
+            [b'0', b'b', ..] => Some(|byte| matches!(byte, b'0' | b'1')),
+            _ => None,
+        };
+        let radix_digit = radix_digit.filter(|is_digit| {
+            self.remainder()
+                .as_bytes()
+                .get(2)
+                .is_some_and(|&byte| is_digit(byte) || byte == b'_')
+        });
+
+        if let Some(is_digit) = radix_digit {
+            self.pos += 2;
+            self.skip_while(|byte| is_digit(byte) || byte == b'_');
+        } else {
+            self.skip_while(|byte| byte.is_ascii_digit() || byte == b'_');
+            let fraction = self.scan_fraction();
+            if self.scan_exponent() || fraction {
+                let value = self.input[start..self.pos].replace('_', "");
+                self.scan_suffix(&[])?;
+                self.push(&TokenKind::Float, value, start);
+                return Ok(());
             }
+        }
+
+        let digits = normalize_integer(&self.input[start..self.pos]);
+        let suffix = self.scan_suffix(&INTEGER_SUFFIXES)?;
+        let token = self.push(&TokenKind::Number, digits, start);
+        token.suffix = suffix;
+        Ok(())
+    }
+
+    /// Consumes the identifier characters right after a number, which must
+    /// spell one of `allowed` or nothing. `10u8x` is one bad suffix rather
+    /// than `10u8` followed by `x`.
+    fn scan_suffix(&mut self, allowed: &[&str]) -> Result<Option<String>, TokenizerError> {
+        let start = self.pos;
+        self.skip_while(|byte| byte.is_ascii_alphanumeric() || byte == b'_');
+        let suffix = &self.input[start..self.pos];
+        if suffix.is_empty() {
+            return Ok(None);
+        }
+        if !allowed.contains(&suffix) {
+            let span = Span::new(self.file, start, self.pos);
+            return Err(TokenizerError::InvalidSuffix(suffix.to_string(), span));
+        }
+        Ok(Some(suffix.to_string()))
+    }
 
-            if let Some(token) = token {
-                lexer.tokens.push(token);
+    /// Consumes a `.` followed by digits, a `.` not followed by a digit is left
+    /// alone so ranges like `0..10` keep working.
+    fn scan_fraction(&mut self) -> bool {
+        match self.remainder().as_bytes() {
+            [b'.', digit, ..] if digit.is_ascii_digit() => {
+                self.pos += 1;
+                self.skip_while(|byte| byte.is_ascii_digit() || byte == b'_');
+                true
             }
-            lexer.pos += advance;
+            _ => false,
         }
+    }
 
-        lexer
-            .tokens
-            .push(Token::new(&TokenKind::Eof, String::new(), lexer.pos, 0));
+    fn scan_exponent(&mut self) -> bool {
+        let sign = match self.remainder().as_bytes() {
+            [b'e' | b'E', b'+' | b'-', ..] => 1,
+            [b'e' | b'E', ..] => 0,
+            _ => return false,
+        };
+        let exponent = self.pos;
+        self.pos += 1 + sign;
+        if self.skip_while(|byte| byte.is_ascii_digit()) == 0 {
+            self.pos = exponent;
+            return false;
+        }
+        true
+    }
 
-        Ok(lexer.tokens)
+    fn scan_symbol(&mut self) {
+        let start = self.pos;
+        self.skip_while(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'!');
+        let value = &self.input[start..self.pos];
+        let kind = TokenKind::is_keyword(value).unwrap_or(TokenKind::Identifier);
+        self.push(&kind, value.to_string(), start);
     }
 }
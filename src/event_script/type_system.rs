@@ -1,37 +1,104 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
-use crate::event_script::ast::{Expression, Statement};
+use crate::event_script::{
+    ast::{Expression, ExpressionKind, Statement, StatementKind},
+    diagnostic::Diagnostic,
+    span::Span,
+    token::{Token, TokenKind},
+    value::IntegerType,
+};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Type {
     pub(crate) name: String,
     size: usize,
+    // Type of the items produced when iterating over a value of this type.
+    element: Option<Box<Type>>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Function {
-    name: String,
+    pub(crate) name: String,
     return_type: Type,
     params: Vec<Type>,
 }
 
+impl Function {
+    #[must_use]
+    fn new<S: ToString>(name: &S, return_type: Type, params: Vec<Type>) -> Self {
+        Self {
+            name: name.to_string(),
+            return_type,
+            params,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Variable {
     pub(crate) name: String,
     is_const: bool,
     mutable: bool,
+    // `None` until the type is inferred from an assignment or a use.
     type_: Option<Type>,
+    // Untyped integer literals assigned while `type_` only holds the default
+    // integer type, with their values. A later use may still pick another type,
+    // they are typed once the type of the variable is fixed.
+    literals: Vec<(Span, i128)>,
+    // Whether the variable is assigned on every path, or on at least one path,
+    // reaching the current point of the program.
+    assigned: bool,
+    possibly_assigned: bool,
+    // Name token of a `let` or `const`, parameters and loop variables have none.
+    declaration: Option<Token>,
 }
 
 impl Variable {
     #[must_use]
-    fn new<S: ToString>(name: &S, is_const: bool, mutable: bool, type_: Option<Type>) -> Self {
+    fn new<S: ToString>(
+        name: &S,
+        is_const: bool,
+        mutable: bool,
+        type_: Option<Type>,
+        assigned: bool,
+    ) -> Self {
         Self {
             name: name.to_string(),
             is_const,
             mutable,
             type_,
+            literals: Vec::new(),
+            assigned,
+            possibly_assigned: assigned,
+            declaration: None,
+        }
+    }
+
+    /// Fixes the type of a variable whose type was unknown or only defaulted from literals.
+    /// Returns the spans of the literals assigned to it so far, which take that type.
+    fn infer(&mut self, type_: Type) -> Result<Vec<Span>, TypeErrors> {
+        let literals = std::mem::take(&mut self.literals);
+        if !literals.is_empty() && !type_.is_error() {
+            let Some((lower, upper)) = type_.integer_bounds() else {
+                return Err(TypeErrors::Mismatch {
+                    expected: type_,
+                    found: self.type_.clone().unwrap_or_else(Type::unit),
+                });
+            };
+            if let Some((_, literal)) = literals
+                .iter()
+                .find(|(_, literal)| !(lower..=upper).contains(literal))
+            {
+                return Err(TypeErrors::LiteralOutOfRange(literal.to_string(), type_));
+            }
         }
+        self.type_ = Some(type_);
+        Ok(literals.into_iter().map(|(span, _)| span).collect())
+    }
+
+    /// Whether the type of the variable can still be inferred from a later use.
+    fn is_open(&self) -> bool {
+        self.type_.is_none() || !self.literals.is_empty()
     }
 }
 
@@ -39,7 +106,7 @@ impl Variable {
 enum SymbolType {
     Type(Type),
     Variable(Variable),
-    // Function(Function),
+    Function(Function),
     // Macro(String, Vec<T>),
 }
 
@@ -58,19 +125,302 @@ impl Key {
     }
 }
 
+/// Assignment state of every variable at one point of the program, used to
+/// join the paths of branches and loops.
+struct FlowState {
+    variables: HashMap<Key, (bool, bool)>,
+    unreachable: bool,
+}
+
 pub struct TypeChecker {
     symbol_table: HashMap<Key, SymbolType>,
+    function_depth: Option<u16>,
+    return_type: Option<Type>,
+    expression_blocks: u16,
+    loop_depth: u16,
+    // Depth of the innermost loop or function, variables declared at or above it
+    // can be assigned once per iteration or call.
+    repeat_depth: Option<u16>,
+    // Set after `return`, `break` and `continue` until control flow joins again.
+    unreachable: bool,
+    // Type inferred for each integer literal, keyed by the span of its digits.
+    literal_types: HashMap<Span, String>,
+    // Set once a statement failed to parse, it may have declared any function.
+    recovered: bool,
 }
 
 #[derive(Debug)]
 pub enum TypeErrors {
-    TypeAlreadyExists(),
-    FunctionAlreadyExists(),
-    VariableAlreadyExists(),
-    OperationIsNotSupported(),
+    TypeAlreadyExists(String),
+    FunctionAlreadyExists(String),
+    VariableAlreadyExists(String),
+    OperationIsNotSupported(TokenKind, Vec<Type>),
     VariableDoesntExist(String),
     TypeNotFound(String),
     SymbolIsNotAType(String, String),
+    SymbolIsNotAVariable(String),
+    SymbolIsNotAFunction(String),
+    ExpressionIsNotCallable(),
+    ArgumentCountMismatch(String, usize, usize),
+    ArgumentTypeMismatch(String, Type, Type),
+    ReturnTypeMismatch(Type, Type),
+    ReturnOutsideFunction(),
+    ReturnInsideExpression(),
+    MissingReturn(String),
+    ConditionIsNotABool(Type),
+    BranchTypeMismatch(Type, Type),
+    BreakOutsideLoop(),
+    ContinueOutsideLoop(),
+    TypeIsNotIterable(Type),
+    LiteralOutOfRange(String, Type),
+    ShiftOutOfRange(String, Type),
+    Mismatch {
+        expected: Type,
+        found: Type,
+    },
+    AmbiguousType(String),
+    InvalidAssignmentTarget(),
+    UseOfPossiblyUninitialized(Token),
+    AssignmentToImmutable {
+        target: Box<Token>,
+        declaration: Option<Box<Token>>,
+        help: String,
+    },
+    ConstNeverAssigned(String),
+}
+
+/// A type error and the innermost statement or expression it was found in.
+#[derive(Debug)]
+pub struct TypeError {
+    pub kind: Box<TypeErrors>,
+    pub span: Option<Span>,
+}
+
+impl TypeError {
+    /// Locates the error at `span` unless a nested node already located it.
+    #[must_use]
+    pub fn at(mut self, span: Span) -> Self {
+        self.span.get_or_insert(span);
+        self
+    }
+}
+
+impl From<TypeErrors> for TypeError {
+    fn from(kind: TypeErrors) -> Self {
+        TypeError {
+            kind: Box::new(kind),
+            span: None,
+        }
+    }
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.kind)
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{count} {singular}")
+    } else {
+        format!("{count} {plural}")
+    }
+}
+
+impl From<&TypeError> for Diagnostic {
+    #[allow(clippy::too_many_lines)]
+    fn from(error: &TypeError) -> Self {
+        let (diagnostic, label) = match error.kind.as_ref() {
+            TypeErrors::TypeAlreadyExists(name) => (
+                Diagnostic::error("E0301", &format!("type `{name}` is defined multiple times")),
+                "redefined here".to_string(),
+            ),
+            TypeErrors::FunctionAlreadyExists(name) => (
+                Diagnostic::error(
+                    "E0302",
+                    &format!("function `{name}` is defined multiple times"),
+                ),
+                "redefined here".to_string(),
+            ),
+            TypeErrors::VariableAlreadyExists(name) => (
+                Diagnostic::error("E0303", &format!("`{name}` is already declared")),
+                "declared again here".to_string(),
+            ),
+            TypeErrors::OperationIsNotSupported(operator, operands) => {
+                let operator = operator
+                    .operator_symbol()
+                    .map_or_else(|| format!("{operator:?}"), ToString::to_string);
+                let listed = operands
+                    .iter()
+                    .map(|operand| format!("`{operand}`"))
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                let mut diagnostic =
+                    Diagnostic::error("E0304", &format!("cannot apply `{operator}` to {listed}"));
+                if let [lhs, rhs] = &operands[..]
+                    && lhs != rhs
+                {
+                    diagnostic = diagnostic.with_note(&"both operands must have the same type");
+                }
+                (diagnostic, "unsupported operation".to_string())
+            }
+            TypeErrors::VariableDoesntExist(name) => (
+                Diagnostic::error("E0305", &format!("cannot find `{name}` in this scope")),
+                "not found in this scope".to_string(),
+            ),
+            TypeErrors::TypeNotFound(name) => (
+                Diagnostic::error("E0306", &format!("cannot find type `{name}`")),
+                "unknown type".to_string(),
+            ),
+            TypeErrors::SymbolIsNotAType(type_name, name) => (
+                Diagnostic::error("E0307", &format!("`{type_name}` is not a type")),
+                format!("used as the type of `{name}`"),
+            ),
+            TypeErrors::SymbolIsNotAVariable(name) => (
+                Diagnostic::error("E0308", &format!("`{name}` is not a variable")),
+                "expected a variable".to_string(),
+            ),
+            TypeErrors::SymbolIsNotAFunction(name) => (
+                Diagnostic::error("E0309", &format!("`{name}` is not a function")),
+                "called here".to_string(),
+            ),
+            TypeErrors::ExpressionIsNotCallable() => (
+                Diagnostic::error("E0310", &"expression cannot be called")
+                    .with_help(&"only functions can be called, by their name"),
+                "not a function".to_string(),
+            ),
+            TypeErrors::ArgumentCountMismatch(name, expected, found) => (
+                Diagnostic::error(
+                    "E0311",
+                    &format!(
+                        "`{name}` takes {} but {} given",
+                        plural(*expected, "argument", "arguments"),
+                        plural(*found, "was", "were"),
+                    ),
+                ),
+                "wrong number of arguments".to_string(),
+            ),
+            TypeErrors::ArgumentTypeMismatch(name, expected, found) => (
+                Diagnostic::error(
+                    "E0312",
+                    &format!("mismatched argument type in call to `{name}`"),
+                ),
+                format!("expected `{expected}`, found `{found}`"),
+            ),
+            TypeErrors::ReturnTypeMismatch(expected, found) => (
+                Diagnostic::error("E0313", &"mismatched return type"),
+                format!("expected `{expected}`, found `{found}`"),
+            ),
+            TypeErrors::ReturnOutsideFunction() => (
+                Diagnostic::error("E0314", &"`return` outside of a function"),
+                "cannot return from here".to_string(),
+            ),
+            TypeErrors::ReturnInsideExpression() => (
+                Diagnostic::error("E0315", &"`return` inside an expression block")
+                    .with_note(&"an expression block always produces a value"),
+                "cannot return from here".to_string(),
+            ),
+            TypeErrors::MissingReturn(name) => (
+                Diagnostic::error(
+                    "E0316",
+                    &format!("function `{name}` does not return a value on every path"),
+                ),
+                "missing `return`".to_string(),
+            ),
+            TypeErrors::ConditionIsNotABool(found) => (
+                Diagnostic::error("E0317", &"condition is not a `bool`"),
+                format!("expected `bool`, found `{found}`"),
+            ),
+            TypeErrors::BranchTypeMismatch(body, otherwise) => (
+                Diagnostic::error("E0318", &"`if` and `else` have different types"),
+                format!("`if` is `{body}`, `else` is `{otherwise}`"),
+            ),
+            TypeErrors::BreakOutsideLoop() => (
+                Diagnostic::error("E0319", &"`break` outside of a loop"),
+                "cannot break from here".to_string(),
+            ),
+            TypeErrors::ContinueOutsideLoop() => (
+                Diagnostic::error("E0320", &"`continue` outside of a loop"),
+                "cannot continue from here".to_string(),
+            ),
+            TypeErrors::TypeIsNotIterable(found) => (
+                Diagnostic::error("E0321", &format!("`{found}` is not iterable"))
+                    .with_help(&"iterate over a range such as `0..10`"),
+                "cannot iterate over this".to_string(),
+            ),
+            TypeErrors::LiteralOutOfRange(value, type_) => {
+                let mut diagnostic =
+                    Diagnostic::error("E0322", &format!("literal out of range for `{type_}`"));
+                if let Some((min, max)) = type_.integer_bounds() {
+                    diagnostic =
+                        diagnostic.with_note(&format!("`{type_}` ranges from {min} to {max}"));
+                }
+                (diagnostic, format!("`{value}` does not fit in `{type_}`"))
+            }
+            TypeErrors::ShiftOutOfRange(amount, type_) => (
+                Diagnostic::error("E0323", &format!("shift amount out of range for `{type_}`"))
+                    .with_note(&format!(
+                        "`{type_}` can be shifted by 0 to {}",
+                        type_.size.saturating_sub(1)
+                    )),
+                format!("shifting by {amount}"),
+            ),
+            TypeErrors::Mismatch { expected, found } => (
+                Diagnostic::error("E0324", &"mismatched types"),
+                format!("expected `{expected}`, found `{found}`"),
+            ),
+            TypeErrors::AmbiguousType(name) => (
+                Diagnostic::error("E0325", &format!("type annotations needed for `{name}`"))
+                    .with_help(&format!("give it a type, e.g. `let {name}: i32`")),
+                "type cannot be inferred".to_string(),
+            ),
+            TypeErrors::InvalidAssignmentTarget() => (
+                Diagnostic::error("E0326", &"invalid left-hand side of assignment"),
+                "cannot assign to this expression".to_string(),
+            ),
+            TypeErrors::UseOfPossiblyUninitialized(name) => (
+                Diagnostic::error(
+                    "E0327",
+                    &format!("use of possibly uninitialized `{}`", name.value),
+                )
+                .with_primary(
+                    name.span(),
+                    &format!("`{}` may be unassigned here", name.value),
+                ),
+                String::new(),
+            ),
+            TypeErrors::AssignmentToImmutable {
+                target,
+                declaration,
+                help,
+            } => {
+                let mut diagnostic = Diagnostic::error(
+                    "E0328",
+                    &format!(
+                        "cannot assign twice to immutable variable `{}`",
+                        target.value
+                    ),
+                )
+                .with_primary(target.span(), &"cannot assign twice")
+                .with_help(help);
+                if let Some(declaration) = declaration {
+                    diagnostic = diagnostic.with_secondary(declaration.span(), &"declared here");
+                }
+                (diagnostic, String::new())
+            }
+            TypeErrors::ConstNeverAssigned(name) => (
+                Diagnostic::error("E0329", &format!("constant `{name}` is never assigned")),
+                "declared here".to_string(),
+            ),
+        };
+        match error.span {
+            Some(span) if diagnostic.primary.is_none() => diagnostic.with_primary(span, &label),
+            _ => diagnostic,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +448,85 @@ impl Type {
         Type {
             name: name.to_string(),
             size,
+            element: None,
+        }
+    }
+
+    #[must_use]
+    pub fn range(element: Type) -> Self {
+        Type {
+            name: "range".to_string(),
+            size: element.size * 2,
+            element: Some(Box::new(element)),
+        }
+    }
+
+    #[must_use]
+    pub fn is_integer(&self) -> bool {
+        IntegerType::from_name(&self.name).is_some()
+    }
+
+    /// Smallest and largest value representable by an integer type.
+    #[must_use]
+    pub fn integer_bounds(&self) -> Option<(i128, i128)> {
+        IntegerType::from_name(&self.name).map(IntegerType::bounds)
+    }
+
+    #[must_use]
+    pub fn is_float(&self) -> bool {
+        matches!(self.name.as_str(), "f32" | "f64")
+    }
+
+    #[must_use]
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+
+    #[must_use]
+    pub fn is_signed(&self) -> bool {
+        self.is_float() || matches!(self.name.as_str(), "i8" | "i16" | "i32" | "i64")
+    }
+
+    /// The type of statements and calls that don't produce a value.
+    #[must_use]
+    pub fn unit() -> Self {
+        Type::new(&"()", 0)
+    }
+
+    #[must_use]
+    pub fn bool() -> Self {
+        Type::new(&"bool", 1)
+    }
+
+    #[must_use]
+    pub fn string() -> Self {
+        Type::new(&"string", 0)
+    }
+
+    /// The type of source that failed to parse. It is compatible with every
+    /// other type so a syntax error doesn't also cause type errors.
+    #[must_use]
+    pub fn error() -> Self {
+        Type::new(&"{error}", 0)
+    }
+
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        self.name == "{error}"
+    }
+
+    /// Whether a value of type `other` can't be used where `self` is expected.
+    #[must_use]
+    pub fn conflicts_with(&self, other: &Type) -> bool {
+        self != other && !self.is_error() && !other.is_error()
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.element {
+            Some(element) => write!(f, "{}<{element}>", self.name),
+            None => write!(f, "{}", self.name),
         }
     }
 }
@@ -106,148 +535,988 @@ impl TypeChecker {
     fn new() -> Self {
         let mut res = TypeChecker {
             symbol_table: HashMap::new(),
+            function_depth: None,
+            return_type: None,
+            expression_blocks: 0,
+            loop_depth: 0,
+            repeat_depth: None,
+            unreachable: false,
+            literal_types: HashMap::new(),
+            recovered: false,
         };
         res.initialize();
 
         res
     }
 
-    /// .
+    /// Checks a whole script in the global scope. Returns the script with the
+    /// type inferred for every integer literal without a suffix written as its
+    /// suffix, so the backends evaluate each literal at the type it was checked as.
     ///
     /// # Errors
     ///
-    /// This function will return an error if .
-    pub fn check(statements: Vec<Statement>) -> Result<(), TypeErrors> {
+    /// Returns the first type error, located at the innermost statement or
+    /// expression it was found in.
+    pub fn check(mut statements: Vec<Statement>) -> Result<Vec<Statement>, TypeError> {
         let mut checker = TypeChecker::new();
-        for stmt in statements {
+        for stmt in statements.clone() {
             checker.populate_table(stmt, 0)?;
         }
-        println!(
-            "{:?}",
-            checker
-                .symbol_table
-                .iter()
-                .filter_map(|(_, val)| {
-                    if matches!(val, SymbolType::Variable(_)) {
-                        return Some(val);
-                    }
-                    None
-                })
-                .collect::<Vec<_>>()
-        );
-        Ok(())
+        checker.settle_scope_literals(0)?;
+        checker.check_scope_variables(0)?;
+        for statement in &mut statements {
+            checker.annotate_statement(statement);
+        }
+        Ok(statements)
+    }
+
+    /// Writes the inferred types of the integer literals of a statement into their suffixes.
+    fn annotate_statement(&self, statement: &mut Statement) {
+        match &mut statement.kind {
+            StatementKind::Expression(expression)
+            | StatementKind::Variable(_, _, _, _, Some(expression))
+            | StatementKind::Return(Some(expression)) => self.annotate_expression(expression),
+            StatementKind::Block(statements) | StatementKind::Function(_, _, _, statements) => {
+                self.annotate_statements(statements);
+            }
+            StatementKind::If(condition, body, otherwise) => {
+                self.annotate_expression(condition);
+                self.annotate_statements(body);
+                if let Some(otherwise) = otherwise {
+                    self.annotate_statement(otherwise);
+                }
+            }
+            StatementKind::While(condition, body) | StatementKind::For(_, condition, body) => {
+                self.annotate_expression(condition);
+                self.annotate_statements(body);
+            }
+            StatementKind::Variable(..)
+            | StatementKind::Return(None)
+            | StatementKind::Break
+            | StatementKind::Continue
+            | StatementKind::Error => {}
+        }
     }
 
-    fn populate_table(&mut self, statement: Statement, depth: u16) -> Result<(), TypeErrors> {
-        match statement {
-            Statement::Block(statements) => {
-                for statement in statements {
-                    self.populate_table(statement, depth + 1)?;
+    fn annotate_statements(&self, statements: &mut [Statement]) {
+        for statement in statements {
+            self.annotate_statement(statement);
+        }
+    }
+
+    fn annotate_expression(&self, mut expression: &mut Expression) {
+        // Chains of binary operations are walked down in a loop, they can be long.
+        loop {
+            match &mut expression.kind {
+                ExpressionKind::Binary(lhs, _, rhs) => {
+                    self.annotate_expression(rhs);
+                    expression = lhs;
+                    continue;
+                }
+                ExpressionKind::Number(_, suffix @ None) => {
+                    *suffix = self.literal_types.get(&expression.span).cloned();
+                }
+                ExpressionKind::Groupping(expression)
+                | ExpressionKind::Unary(_, expression)
+                | ExpressionKind::Increment(expression, _, _) => {
+                    self.annotate_expression(expression)
+                }
+                ExpressionKind::Assignment(lhs, rhs)
+                | ExpressionKind::CompoundAssignment(lhs, _, rhs) => {
+                    self.annotate_expression(lhs);
+                    self.annotate_expression(rhs);
+                }
+                ExpressionKind::FunctionCall(callee, arguments) => {
+                    self.annotate_expression(callee);
+                    for argument in arguments {
+                        self.annotate_expression(argument);
+                    }
+                }
+                ExpressionKind::Block(statements, tail) => {
+                    self.annotate_statements(statements);
+                    if let Some(tail) = tail {
+                        self.annotate_expression(tail);
+                    }
+                }
+                ExpressionKind::If(condition, body, otherwise) => {
+                    self.annotate_expression(condition);
+                    self.annotate_expression(body);
+                    self.annotate_expression(otherwise);
                 }
+                ExpressionKind::String(_)
+                | ExpressionKind::Number(..)
+                | ExpressionKind::Float(_)
+                | ExpressionKind::Bool(_)
+                | ExpressionKind::Symbol(_)
+                | ExpressionKind::Error => {}
+            }
+            return;
+        }
+    }
+
+    fn populate_table(&mut self, statement: Statement, depth: u16) -> Result<(), TypeError> {
+        let span = statement.span;
+        self.check_statement(statement, depth)
+            .map_err(|error| error.at(span))
+    }
+
+    fn check_statement(&mut self, statement: Statement, depth: u16) -> Result<(), TypeError> {
+        match statement.kind {
+            StatementKind::Expression(expression) => {
+                self.solve_expression_type(expression, depth, None)?;
                 Ok(())
             }
-            Statement::Variable(name, is_const, is_mutable, explicit_type, expression) => {
-                let mut var_type = None;
-                if let Some(type_name) = explicit_type {
-                    if let Some(symbol) = self.symbol_table.get(&Key::new(&name, depth)) {
-                        match symbol {
-                            SymbolType::Type(known_type) => var_type = Some((*known_type).clone()),
-                            _ => {
-                                return Err(TypeErrors::SymbolIsNotAType(
-                                    type_name.clone(),
-                                    name.clone(),
-                                ));
-                            }
-                        }
-                    } else {
-                        return Err(TypeErrors::TypeNotFound(type_name));
+            StatementKind::Block(statements) => {
+                let result = statements
+                    .into_iter()
+                    .try_for_each(|statement| self.populate_table(statement, depth + 1));
+                let scope = self.leave_scope(depth);
+                result.and(scope)
+            }
+            StatementKind::Variable(name, is_const, is_mutable, explicit_type, expression) => {
+                let annotation = match explicit_type {
+                    Some(type_name) => Some(self.resolve_type(&type_name, &name.value, depth)?),
+                    None => None,
+                };
+                let initializer_span = expression.as_ref().map(|expr| expr.span);
+                let initializer = match expression {
+                    Some(expr) => {
+                        Some(self.solve_expression_type(expr, depth, annotation.as_ref())?)
+                    }
+                    None => None,
+                };
+                let assigned = initializer.is_some();
+                let var_type = match (annotation, initializer_span.zip(initializer)) {
+                    (Some(expected), Some((span, found))) if expected.conflicts_with(&found) => {
+                        return Err(
+                            TypeError::from(TypeErrors::Mismatch { expected, found }).at(span)
+                        );
+                    }
+                    (annotation, initializer) => annotation.or(initializer.map(|(_, found)| found)),
+                };
+                self.add_variable(&name.value, is_const, is_mutable, var_type, assigned, depth)?;
+                let variable = self.lookup_variable(&name.value, depth)?;
+                variable.declaration = Some(name);
+                Ok(())
+            }
+            StatementKind::Function(name, params, return_type, body) => {
+                let return_type = match return_type {
+                    Some(type_name) => self.resolve_type(&type_name, &name, depth)?,
+                    None => Type::unit(),
+                };
+                let mut param_types = Vec::new();
+                for (param, type_name) in &params {
+                    param_types.push(self.resolve_type(type_name, param, depth)?);
+                }
+                if return_type != Type::unit() && !Self::always_returns(&body) {
+                    return Err(TypeErrors::MissingReturn(name).into());
+                }
+                self.add_function(&name, return_type.clone(), param_types.clone(), depth)?;
+
+                let outer_depth = self.function_depth.replace(depth);
+                let outer_return_type = self.return_type.replace(return_type);
+                let outer_expression_blocks = std::mem::take(&mut self.expression_blocks);
+                let outer_loop_depth = std::mem::take(&mut self.loop_depth);
+                let outer_repeat_depth = self.repeat_depth.replace(depth);
+                // Assignments inside the body don't happen at the declaration.
+                let outer_flow = self.flow_state();
+                self.unreachable = false;
+                let result = params
+                    .iter()
+                    .zip(param_types)
+                    .try_for_each(|((param, _), type_)| {
+                        self.add_variable(param, false, false, Some(type_), true, depth + 1)
+                    })
+                    .map_err(TypeError::from)
+                    .and_then(|()| {
+                        body.into_iter()
+                            .try_for_each(|statement| self.populate_table(statement, depth + 1))
+                    });
+                let result = result.and(self.leave_scope(depth));
+                self.function_depth = outer_depth;
+                self.return_type = outer_return_type;
+                self.expression_blocks = outer_expression_blocks;
+                self.loop_depth = outer_loop_depth;
+                self.repeat_depth = outer_repeat_depth;
+                self.restore_flow(outer_flow);
+                result
+            }
+            StatementKind::Return(expression) => {
+                let expected = self
+                    .return_type
+                    .clone()
+                    .ok_or(TypeErrors::ReturnOutsideFunction())?;
+                if self.expression_blocks > 0 {
+                    return Err(TypeErrors::ReturnInsideExpression().into());
+                }
+                let span = expression.as_ref().map_or(statement.span, |expr| expr.span);
+                let found = match expression {
+                    Some(expression) => {
+                        self.solve_expression_type(expression, depth, Some(&expected))?
                     }
+                    None => Type::unit(),
+                };
+                if expected.conflicts_with(&found) {
+                    return Err(
+                        TypeError::from(TypeErrors::ReturnTypeMismatch(expected, found)).at(span),
+                    );
                 }
-                if let Some(expr) = expression {
-                    var_type = Some(self.solve_expression_type(expr, depth)?);
+                self.unreachable = true;
+                Ok(())
+            }
+            StatementKind::If(condition, body, otherwise) => {
+                self.check_condition(condition, depth)?;
+                let before = self.flow_state();
+                let result = body
+                    .into_iter()
+                    .try_for_each(|statement| self.populate_table(statement, depth + 1));
+                let scope = self.leave_scope(depth);
+                result.and(scope)?;
+                let after_body = self.flow_state();
+                self.restore_flow(before);
+                if let Some(otherwise) = otherwise {
+                    self.populate_table(*otherwise, depth)?;
                 }
-                self.add_variable(&name, is_const, is_mutable, var_type, depth)?;
+                let after_otherwise = self.flow_state();
+                self.merge_flow(after_body, after_otherwise);
+                Ok(())
+            }
+            StatementKind::While(condition, body) => {
+                let before = self.flow_state();
+                let outer_repeat_depth = self.repeat_depth.replace(depth);
+                self.check_condition(condition, depth)?;
+                self.loop_depth += 1;
+                let result = body
+                    .into_iter()
+                    .try_for_each(|statement| self.populate_table(statement, depth + 1));
+                self.loop_depth -= 1;
+                self.repeat_depth = outer_repeat_depth;
+                let scope = self.leave_scope(depth);
+                // The body may not run at all, so nothing it assigns is definitely assigned.
+                self.restore_flow(before);
+                result.and(scope)
+            }
+            StatementKind::For(name, iterable, body) => {
+                let iterable_span = iterable.span;
+                let iterable = self.solve_expression_type(iterable, depth, None)?;
+                let element = match &iterable.element {
+                    _ if iterable.is_error() => Some(Box::new(Type::error())),
+                    element => element.clone(),
+                };
+                let Some(element) = element else {
+                    return Err(
+                        TypeError::from(TypeErrors::TypeIsNotIterable(iterable)).at(iterable_span)
+                    );
+                };
+                let before = self.flow_state();
+                let outer_repeat_depth = self.repeat_depth.replace(depth);
+                self.loop_depth += 1;
+                let result = self
+                    .add_variable(&name, false, false, Some(*element), true, depth + 1)
+                    .map_err(TypeError::from)
+                    .and_then(|()| {
+                        body.into_iter()
+                            .try_for_each(|statement| self.populate_table(statement, depth + 1))
+                    });
+                self.loop_depth -= 1;
+                self.repeat_depth = outer_repeat_depth;
+                let scope = self.leave_scope(depth);
+                self.restore_flow(before);
+                result.and(scope)
+            }
+            StatementKind::Break if self.loop_depth == 0 => {
+                Err(TypeErrors::BreakOutsideLoop().into())
+            }
+            StatementKind::Continue if self.loop_depth == 0 => {
+                Err(TypeErrors::ContinueOutsideLoop().into())
+            }
+            StatementKind::Break | StatementKind::Continue => {
+                self.unreachable = true;
+                Ok(())
+            }
+            StatementKind::Error => {
+                self.assume_assigned_by_error();
+                self.recovered = true;
                 Ok(())
             }
-            _ => Ok(()),
         }
     }
-    // fn check_type_correctness(&mut self) {}
-    fn solve_expression_type(
+
+    fn check_condition(&mut self, condition: Expression, depth: u16) -> Result<(), TypeError> {
+        let span = condition.span;
+        let condition = self.solve_expression_type(condition, depth, Some(&Type::bool()))?;
+        if condition.conflicts_with(&Type::bool()) {
+            return Err(TypeError::from(TypeErrors::ConditionIsNotABool(condition)).at(span));
+        }
+        Ok(())
+    }
+
+    fn always_returns(statements: &[Statement]) -> bool {
+        statements.iter().any(|statement| match &statement.kind {
+            // A statement that failed to parse may have been a `return`.
+            StatementKind::Return(_) | StatementKind::Error => true,
+            StatementKind::Block(statements) => Self::always_returns(statements),
+            StatementKind::If(_, body, Some(otherwise)) => {
+                Self::always_returns(body) && Self::always_returns(std::slice::from_ref(otherwise))
+            }
+            _ => false,
+        })
+    }
+
+    /// Finds the innermost symbol with the given name visible from `depth`.
+    /// Inside a function body only globals and the function's own scopes are visible.
+    fn lookup(&self, name: &str, depth: u16) -> Option<&SymbolType> {
+        self.lookup_key(name, depth)
+            .and_then(|key| self.symbol_table.get(&key))
+    }
+
+    fn lookup_key(&self, name: &str, depth: u16) -> Option<Key> {
+        self.symbol_table
+            .keys()
+            .filter(|key| key.name == name && key.depth <= depth)
+            .filter(|key| {
+                self.function_depth
+                    .is_none_or(|base| key.depth == 0 || key.depth > base)
+            })
+            .max_by_key(|key| key.depth)
+            .cloned()
+    }
+
+    fn lookup_variable(&mut self, name: &str, depth: u16) -> Result<&mut Variable, TypeErrors> {
+        let key = self
+            .lookup_key(name, depth)
+            .ok_or_else(|| TypeErrors::VariableDoesntExist(name.to_string()))?;
+        match self.symbol_table.get_mut(&key) {
+            Some(SymbolType::Variable(variable)) => Ok(variable),
+            _ => Err(TypeErrors::SymbolIsNotAVariable(name.to_string())),
+        }
+    }
+
+    /// Solves the type of a variable read. A variable whose type is still open
+    /// takes the expected type, after which its type is fixed.
+    fn variable_type(
         &mut self,
-        expression: Expression,
+        name: &Token,
         depth: u16,
+        expected: Option<&Type>,
     ) -> Result<Type, TypeErrors> {
-        match expression {
-            Expression::String(_) => todo!(),
-            Expression::Number(value) => {
-                if value.parse::<i8>().is_ok() {
-                    return Ok(Type::new(&"i8", 8));
-                }
-                if value.parse::<i16>().is_ok() {
-                    return Ok(Type::new(&"i16", 16));
+        let unreachable = self.unreachable;
+        let variable = self.lookup_variable(&name.value, depth)?;
+        if !variable.assigned && !unreachable {
+            return Err(TypeErrors::UseOfPossiblyUninitialized(name.clone()));
+        }
+        let literals = match (expected, variable.type_.clone()) {
+            (Some(expected), current)
+                if variable.is_open() && (current.is_none() || expected.is_integer()) =>
+            {
+                variable.infer(expected.clone())?
+            }
+            // Any other use fixes the default type of the literals assigned so far.
+            (_, Some(current)) => variable.infer(current)?,
+            (_, None) => return Err(TypeErrors::AmbiguousType(name.value.clone())),
+        };
+        let type_ = variable.type_.clone().unwrap_or_else(Type::error);
+        self.record_literals(literals, &type_);
+        Ok(type_)
+    }
+
+    /// Records the type of integer literals whose type was only fixed after they were checked.
+    fn record_literals(&mut self, spans: Vec<Span>, type_: &Type) {
+        if type_.is_error() {
+            return;
+        }
+        for span in spans {
+            self.literal_types.insert(span, type_.name.clone());
+        }
+    }
+
+    /// Solves the type of an assignment, inferring the type of a variable
+    /// declared without a type or initializer from the assigned value.
+    fn assignment_type(
+        &mut self,
+        target: &Token,
+        value: Expression,
+        depth: u16,
+    ) -> Result<Type, TypeError> {
+        let name = target.value.as_str();
+        let variable = self.lookup_variable(name, depth)?;
+        let current = variable.type_.clone();
+        let open = variable.is_open();
+
+        // The literal is typed once a later use fixes the type of the variable,
+        // until then the variable holds the default integer type.
+        if let Some(literal) = Self::untyped_literal(&value).filter(|_| open) {
+            let variable = self.mark_assigned(target, depth)?;
+            variable.literals.push(literal);
+            let type_ = variable
+                .type_
+                .get_or_insert_with(|| Type::new(&"i32", 32))
+                .clone();
+            return Ok(type_);
+        }
+
+        let found = self.solve_expression_type(value, depth, current.as_ref())?;
+        let variable = self.mark_assigned(target, depth)?;
+        match current {
+            _ if open => {
+                let literals = variable.infer(found.clone())?;
+                self.record_literals(literals, &found);
+            }
+            Some(expected) if expected.conflicts_with(&found) => {
+                return Err(TypeErrors::Mismatch { expected, found }.into());
+            }
+            _ => {}
+        }
+        Ok(found)
+    }
+
+    /// Records an assignment to a variable. Constants and variables declared
+    /// without `mut` may only be assigned once, to initialize them.
+    fn mark_assigned(&mut self, target: &Token, depth: u16) -> Result<&mut Variable, TypeErrors> {
+        let repeated = self
+            .lookup_key(&target.value, depth)
+            .zip(self.repeat_depth)
+            .is_some_and(|(key, repeat_depth)| key.depth <= repeat_depth);
+        let variable = self.lookup_variable(&target.value, depth)?;
+        let immutable = variable.is_const || !variable.mutable;
+        if immutable && (variable.possibly_assigned || repeated) {
+            let name = &variable.name;
+            let help = match &variable.declaration {
+                Some(_) if variable.is_const => {
+                    format!("declare it with `let mut {name}` instead of `const` to reassign it")
                 }
-                if value.parse::<i32>().is_ok() {
-                    return Ok(Type::new(&"i32", 32));
+                Some(_) => format!("make the variable mutable with `let mut {name}`"),
+                None => format!("copy it into a mutable variable with `let mut {name} = {name};`"),
+            };
+            return Err(TypeErrors::AssignmentToImmutable {
+                target: Box::new(target.clone()),
+                declaration: variable.declaration.clone().map(Box::new),
+                help,
+            });
+        }
+        variable.assigned = true;
+        variable.possibly_assigned = true;
+        Ok(variable)
+    }
+
+    /// Source that failed to parse may have assigned any variable in scope, so
+    /// each of them counts as assigned and one without a type takes the error type.
+    fn assume_assigned_by_error(&mut self) {
+        for symbol in self.symbol_table.values_mut() {
+            if let SymbolType::Variable(variable) = symbol {
+                variable.assigned = true;
+                variable.type_.get_or_insert_with(Type::error);
+            }
+        }
+    }
+
+    fn flow_state(&self) -> FlowState {
+        let variables = self
+            .symbol_table
+            .iter()
+            .filter_map(|(key, symbol)| match symbol {
+                SymbolType::Variable(variable) => {
+                    Some((key.clone(), (variable.assigned, variable.possibly_assigned)))
                 }
-                if value.parse::<i64>().is_ok() {
-                    return Ok(Type::new(&"i64", 64));
+                _ => None,
+            })
+            .collect();
+        FlowState {
+            variables,
+            unreachable: self.unreachable,
+        }
+    }
+
+    fn restore_flow(&mut self, state: FlowState) {
+        for (key, symbol) in &mut self.symbol_table {
+            if let SymbolType::Variable(variable) = symbol
+                && let Some((assigned, possibly_assigned)) = state.variables.get(key)
+            {
+                variable.assigned = *assigned;
+                variable.possibly_assigned = *possibly_assigned;
+            }
+        }
+        self.unreachable = state.unreachable;
+    }
+
+    /// Joins two control flow paths, a path that can't reach the join point is ignored.
+    fn merge_flow(&mut self, lhs: FlowState, rhs: FlowState) {
+        if lhs.unreachable {
+            return self.restore_flow(rhs);
+        }
+        if rhs.unreachable {
+            return self.restore_flow(lhs);
+        }
+        let variables = lhs
+            .variables
+            .into_iter()
+            .map(|(key, (assigned, possibly_assigned))| {
+                let (other, possibly_other) = rhs
+                    .variables
+                    .get(&key)
+                    .copied()
+                    .unwrap_or((assigned, possibly_assigned));
+                (
+                    key,
+                    (assigned && other, possibly_assigned || possibly_other),
+                )
+            })
+            .collect();
+        self.restore_flow(FlowState {
+            variables,
+            unreachable: false,
+        });
+    }
+
+    /// Span and value of an integer literal without a suffix, possibly negated.
+    fn untyped_literal(expression: &Expression) -> Option<(Span, i128)> {
+        match &expression.kind {
+            ExpressionKind::Number(value, None) => Some((expression.span, value.parse().ok()?)),
+            ExpressionKind::Unary(operator, expression) if operator.kind == TokenKind::Minus => {
+                Self::untyped_literal(expression).map(|(span, literal)| (span, -literal))
+            }
+            ExpressionKind::Groupping(expression) => Self::untyped_literal(expression),
+            _ => None,
+        }
+    }
+
+    /// Fails if a variable declared at `depth` or deeper never received a type,
+    /// or is a `const` that isn't assigned by the end of its scope.
+    fn check_scope_variables(&self, depth: u16) -> Result<(), TypeError> {
+        let variables = self
+            .symbol_table
+            .iter()
+            .filter(|(key, _)| key.depth >= depth)
+            .filter_map(|(_, symbol)| match symbol {
+                SymbolType::Variable(variable) => Some(variable),
+                _ => None,
+            });
+        for variable in variables {
+            let error = if variable.type_.is_none() {
+                TypeErrors::AmbiguousType(variable.name.clone())
+            } else if variable.is_const && !variable.assigned && !self.unreachable {
+                TypeErrors::ConstNeverAssigned(variable.name.clone())
+            } else {
+                continue;
+            };
+            let span = variable.declaration.as_ref().map(Token::span);
+            return Err(TypeError {
+                kind: Box::new(error),
+                span,
+            });
+        }
+        Ok(())
+    }
+
+    fn resolve_type(&self, type_name: &str, name: &str, depth: u16) -> Result<Type, TypeErrors> {
+        match self.lookup(type_name, depth) {
+            Some(SymbolType::Type(known_type)) => Ok(known_type.clone()),
+            Some(_) => Err(TypeErrors::SymbolIsNotAType(
+                type_name.to_string(),
+                name.to_string(),
+            )),
+            None => Err(TypeErrors::TypeNotFound(type_name.to_string())),
+        }
+    }
+
+    /// Gives the literals assigned to variables declared at `depth` or deeper,
+    /// whose type no use fixed, the default integer type they hold.
+    fn settle_scope_literals(&mut self, depth: u16) -> Result<(), TypeError> {
+        let mut settled = Vec::new();
+        for (key, symbol) in &mut self.symbol_table {
+            if let SymbolType::Variable(variable) = symbol
+                && key.depth >= depth
+                && let Some(type_) = variable.type_.clone().filter(|_| variable.is_open())
+            {
+                let span = variable.declaration.as_ref().map(Token::span);
+                let spans = variable.infer(type_.clone()).map_err(|error| TypeError {
+                    kind: Box::new(error),
+                    span,
+                })?;
+                settled.push((spans, type_));
+            }
+        }
+        for (spans, type_) in settled {
+            self.record_literals(spans, &type_);
+        }
+        Ok(())
+    }
+
+    /// Drops every symbol declared deeper than `depth` once its scope ends.
+    /// Fails if one of the dropped variables never received a type.
+    fn leave_scope(&mut self, depth: u16) -> Result<(), TypeError> {
+        let inferred = self
+            .settle_scope_literals(depth + 1)
+            .and_then(|()| self.check_scope_variables(depth + 1));
+        self.symbol_table.retain(|key, _| key.depth <= depth);
+        inferred
+    }
+    /// Types an integer literal, `negative` when it is the operand of a unary minus.
+    /// Literals without a suffix take the expected integer type and default to `i32`.
+    fn literal_type(
+        &self,
+        value: &str,
+        suffix: Option<&str>,
+        negative: bool,
+        expected: Option<&Type>,
+        depth: u16,
+    ) -> Result<Type, TypeErrors> {
+        let type_ = match (suffix, expected) {
+            (Some(suffix), _) => self.resolve_type(suffix, value, depth)?,
+            (None, Some(expected)) if expected.is_integer() => expected.clone(),
+            (None, _) => Type::new(&"i32", 32),
+        };
+        let literal = value
+            .parse::<i128>()
+            .map(|literal| if negative { -literal } else { literal });
+        match (literal, type_.integer_bounds()) {
+            (Ok(literal), Some((min, max))) if (min..=max).contains(&literal) => Ok(type_),
+            _ => Err(TypeErrors::LiteralOutOfRange(value.to_string(), type_)),
+        }
+    }
+
+    /// Whether an expression is built only from integer literals without a suffix,
+    /// so its type should be taken from the other side of an operation.
+    fn is_untyped_literal(expression: &Expression) -> bool {
+        use crate::event_script::token::TokenKind::{
+            Ampersand, Caret, Minus, Percent, Pipe, Plus, ShiftLeft, ShiftRight, Slash, Star,
+        };
+        // Left operands are walked down in a loop, chains of operations can be long.
+        let mut expression = expression;
+        loop {
+            expression = match &expression.kind {
+                ExpressionKind::Number(_, suffix) => return suffix.is_none(),
+                ExpressionKind::Groupping(expression) | ExpressionKind::Unary(_, expression) => {
+                    expression
                 }
-                Err(TypeErrors::TypeNotFound("i?".to_string())) // todo: make proper error
+                ExpressionKind::Binary(
+                    lhs,
+                    Plus | Minus | Star | Slash | Percent | Ampersand | Pipe | Caret,
+                    rhs,
+                ) if Self::is_untyped_literal(rhs) => lhs,
+                // The shift amount doesn't affect the type of a shift.
+                ExpressionKind::Binary(lhs, ShiftLeft | ShiftRight, _) => lhs,
+                _ => return false,
+            };
+        }
+    }
+
+    // fn check_type_correctness(&mut self) {}
+    /// Solves the type of an expression. `expected` is the type required by the
+    /// surrounding context, if any, and is used to type integer literals.
+    fn solve_expression_type(
+        &mut self,
+        expression: Expression,
+        depth: u16,
+        expected: Option<&Type>,
+    ) -> Result<Type, TypeError> {
+        let span = expression.span;
+        self.expression_type(expression, depth, expected)
+            .map_err(|error| error.at(span))
+    }
+
+    fn expression_type(
+        &mut self,
+        expression: Expression,
+        depth: u16,
+        expected: Option<&Type>,
+    ) -> Result<Type, TypeError> {
+        let span = expression.span;
+        match expression.into_kind() {
+            ExpressionKind::String(_) => Ok(Type::string()),
+            ExpressionKind::Number(value, suffix) => {
+                let type_ = self.literal_type(&value, suffix.as_deref(), false, expected, depth)?;
+                self.literal_types.insert(span, type_.name.clone());
+                Ok(type_)
             }
-            Expression::Float(value) => {
-                if value.parse::<f32>().is_ok() {
+            ExpressionKind::Float(value) => {
+                if expected.is_some_and(|expected| expected.name == "f64") {
+                    return Ok(Type::new(&"f64", 64));
+                }
+                if value.parse::<f32>().is_ok_and(f32::is_finite) {
                     return Ok(Type::new(&"f32", 32));
                 }
                 if value.parse::<f64>().is_ok() {
                     return Ok(Type::new(&"f64", 64));
                 }
-                Err(TypeErrors::TypeNotFound("f?".to_string())) // todo: make proper error
+                Err(TypeErrors::TypeNotFound("f?".to_string()).into()) // todo: make proper error
             }
-            Expression::Symbol(name) => {
-                let symbols: HashMap<String, SymbolType> = self
-                    .symbol_table
-                    .iter()
-                    .filter_map(|(key, val)| {
-                        if key.name == name && key.depth <= depth {
-                            return Some((key.name.clone(), (*val).clone()));
-                        }
-                        None
-                    })
-                    .collect();
-
-                if let Some(symbol) = symbols.get(&name) {
-                    match symbol {
-                        SymbolType::Variable(variable) => match &variable.type_ {
-                            Some(type_) => Ok((*type_).clone()),
-                            None => todo!(),
-                        },
-                        _ => {
-                            todo!()
+            ExpressionKind::Symbol(name) => Ok(self.variable_type(&name, depth, expected)?),
+            ExpressionKind::FunctionCall(callee, arguments) => {
+                let ExpressionKind::Symbol(name) = callee.into_kind() else {
+                    return Err(TypeErrors::ExpressionIsNotCallable().into());
+                };
+                let name = name.value;
+                let function = match self.lookup(&name, depth) {
+                    Some(SymbolType::Function(function)) => function.clone(),
+                    Some(_) => return Err(TypeErrors::SymbolIsNotAFunction(name).into()),
+                    None if self.recovered => {
+                        for argument in arguments {
+                            self.solve_expression_type(argument, depth, None)?;
                         }
+                        return Ok(Type::error());
+                    }
+                    None => return Err(TypeErrors::VariableDoesntExist(name).into()),
+                };
+                if function.params.len() != arguments.len() {
+                    return Err(TypeErrors::ArgumentCountMismatch(
+                        name,
+                        function.params.len(),
+                        arguments.len(),
+                    )
+                    .into());
+                }
+                for (param, argument) in function.params.iter().zip(arguments) {
+                    let span = argument.span;
+                    let found = self.solve_expression_type(argument, depth, Some(param))?;
+                    if param.conflicts_with(&found) {
+                        let error = TypeErrors::ArgumentTypeMismatch(name, param.clone(), found);
+                        return Err(TypeError::from(error).at(span));
                     }
-                } else {
-                    Err(TypeErrors::VariableDoesntExist(name.clone()))
                 }
+                Ok(function.return_type)
             }
-            Expression::Assignment(expression) => {
-                Ok(self.solve_expression_type(*expression, depth)?)
+            ExpressionKind::Assignment(target, value) => {
+                let ExpressionKind::Symbol(name) = target.into_kind() else {
+                    return Err(TypeErrors::InvalidAssignmentTarget().into());
+                };
+                self.assignment_type(&name, *value, depth)
             }
-            Expression::Groupping(expression) => self.solve_expression_type(*expression, depth),
-            Expression::Unary(_, expression) => self.solve_expression_type(*expression, depth), //todo:
-            //check if operation is supported
-            Expression::Binary(lhs_expr, _, rhs_expr) => {
-                let lhs = self.solve_expression_type(*lhs_expr, depth)?;
-                let rhs = self.solve_expression_type(*rhs_expr, depth)?;
-                if lhs == rhs {
-                    Ok(lhs)
-                } else {
-                    Err(TypeErrors::OperationIsNotSupported()) //todo: add proper error
+            ExpressionKind::CompoundAssignment(target, operator, value) => {
+                let ExpressionKind::Symbol(name) = target.into_kind() else {
+                    return Err(TypeErrors::InvalidAssignmentTarget().into());
+                };
+                let expected = self.variable_type(&name, depth, None)?;
+                let found = self.solve_expression_type(*value, depth, Some(&expected))?;
+                if !expected.is_numeric() && !expected.is_error() {
+                    let operands = vec![expected, found];
+                    return Err(TypeErrors::OperationIsNotSupported(operator, operands).into());
                 }
+                if expected.conflicts_with(&found) {
+                    return Err(TypeErrors::Mismatch { expected, found }.into());
+                }
+                self.mark_assigned(&name, depth)?;
+                Ok(expected)
+            }
+            ExpressionKind::Increment(target, operator, _) => {
+                let ExpressionKind::Symbol(name) = target.into_kind() else {
+                    return Err(TypeErrors::InvalidAssignmentTarget().into());
+                };
+                let type_ = self.variable_type(&name, depth, None)?;
+                if !type_.is_numeric() && !type_.is_error() {
+                    return Err(TypeErrors::OperationIsNotSupported(operator, vec![type_]).into());
+                }
+                self.mark_assigned(&name, depth)?;
+                Ok(type_)
+            }
+            ExpressionKind::Groupping(expression) => {
+                self.solve_expression_type(*expression, depth, expected)
+            }
+            ExpressionKind::Bool(_) => Ok(Type::bool()),
+            ExpressionKind::Error => {
+                self.assume_assigned_by_error();
+                Ok(Type::error())
+            }
+            ExpressionKind::Unary(operator, expression) => {
+                let operand = match (&operator.kind, &expression.kind) {
+                    (TokenKind::Minus, ExpressionKind::Number(value, suffix)) => {
+                        let type_ =
+                            self.literal_type(value, suffix.as_deref(), true, expected, depth)?;
+                        self.literal_types
+                            .insert(expression.span, type_.name.clone());
+                        type_
+                    }
+                    _ => self.solve_expression_type(*expression, depth, expected)?,
+                };
+                match operator.kind {
+                    _ if operand.is_error() => Ok(operand),
+                    TokenKind::Not if operand == Type::bool() => Ok(operand),
+                    TokenKind::Minus if operand.is_signed() => Ok(operand),
+                    TokenKind::Tilde if operand.is_integer() => Ok(operand),
+                    kind => Err(TypeErrors::OperationIsNotSupported(kind, vec![operand]).into()),
+                }
+            }
+            ExpressionKind::Binary(lhs, operator, rhs) => {
+                self.binary_type(*lhs, operator, *rhs, span, depth, expected)
+            }
+            ExpressionKind::Block(statements, tail) => {
+                // Loops don't extend into expression blocks, an expression can't be broken out of.
+                let outer_loop_depth = std::mem::take(&mut self.loop_depth);
+                self.expression_blocks += 1;
+                let result = statements
+                    .into_iter()
+                    .try_for_each(|statement| self.populate_table(statement, depth + 1))
+                    .and_then(|()| match tail {
+                        Some(tail) => self.solve_expression_type(*tail, depth + 1, expected),
+                        None => Ok(Type::unit()),
+                    });
+                self.expression_blocks -= 1;
+                self.loop_depth = outer_loop_depth;
+                let scope = self.leave_scope(depth);
+                result.and_then(|type_| scope.map(|()| type_))
+            }
+            ExpressionKind::If(condition, body, otherwise) => {
+                self.check_condition(*condition, depth)?;
+                let before = self.flow_state();
+                let body = self.solve_expression_type(*body, depth, expected)?;
+                let after_body = self.flow_state();
+                self.restore_flow(before);
+                let otherwise =
+                    self.solve_expression_type(*otherwise, depth, expected.or(Some(&body)))?;
+                let after_otherwise = self.flow_state();
+                self.merge_flow(after_body, after_otherwise);
+                if body.conflicts_with(&otherwise) {
+                    return Err(TypeErrors::BranchTypeMismatch(body, otherwise).into());
+                }
+                Ok(if body.is_error() { otherwise } else { body })
             }
         }
     }
 
+    /// Types a chain of binary operations like `a + b - c`. The left operands
+    /// are solved first, walking down the chain in a loop so a long chain
+    /// doesn't recurse once per operator.
+    fn binary_type(
+        &mut self,
+        lhs: Expression,
+        operator: TokenKind,
+        rhs: Expression,
+        span: Span,
+        depth: u16,
+        expected: Option<&Type>,
+    ) -> Result<Type, TypeError> {
+        let mut operations = Vec::new();
+        let mut operation = (lhs, operator, rhs, span);
+        let mut expected = expected.cloned();
+        let mut type_ = loop {
+            let (lhs, operator, rhs, span) = operation;
+            let lhs_expected = match operator {
+                TokenKind::And | TokenKind::Or => Some(Type::bool()),
+                TokenKind::ShiftLeft | TokenKind::ShiftRight => expected,
+                // Literals take their type from the other operand, so solve that one first.
+                _ if !Self::is_untyped_literal(&rhs) && Self::is_untyped_literal(&lhs) => {
+                    let hint = Self::operand_hint(&operator, expected.as_ref());
+                    let rhs = self.solve_expression_type(rhs, depth, hint.as_ref())?;
+                    let lhs = self.solve_expression_type(lhs, depth, Some(&rhs))?;
+                    break Self::operation_type(lhs, operator, rhs)
+                        .map_err(|error| TypeError::from(error).at(span))?;
+                }
+                _ => Self::operand_hint(&operator, expected.as_ref()),
+            };
+            operations.push((operator, rhs, span));
+            let lhs_span = lhs.span;
+            match lhs.into_kind() {
+                ExpressionKind::Binary(next_lhs, next_operator, next_rhs) => {
+                    operation = (*next_lhs, next_operator, *next_rhs, lhs_span);
+                    expected = lhs_expected;
+                }
+                kind => {
+                    let lhs = Expression::new(kind, lhs_span);
+                    break self.solve_expression_type(lhs, depth, lhs_expected.as_ref())?;
+                }
+            }
+        };
+        for (operator, rhs, span) in operations.into_iter().rev() {
+            type_ = self
+                .right_operand_type(type_, operator, rhs, depth)
+                .map_err(|error| error.at(span))?;
+        }
+        Ok(type_)
+    }
+
+    /// Type a binary operation expects its left operand to have, if any.
+    fn operand_hint(operator: &TokenKind, expected: Option<&Type>) -> Option<Type> {
+        use crate::event_script::token::TokenKind::{
+            Ampersand, Caret, DotDot, DotDotEquals, Minus, Percent, Pipe, Plus, Slash, Star,
+        };
+        match operator {
+            Plus | Minus | Star | Slash | Percent | Ampersand | Pipe | Caret => expected.cloned(),
+            DotDot | DotDotEquals => expected.and_then(|range| range.element.as_deref().cloned()),
+            _ => None,
+        }
+    }
+
+    /// Solves the right operand of a binary operation whose left operand is
+    /// of type `lhs` and types the operation.
+    fn right_operand_type(
+        &mut self,
+        lhs: Type,
+        operator: TokenKind,
+        rhs: Expression,
+        depth: u16,
+    ) -> Result<Type, TypeError> {
+        match operator {
+            TokenKind::And | TokenKind::Or => {
+                // The right operand only runs when the left one doesn't decide the result.
+                let before = self.flow_state();
+                let rhs = self.solve_expression_type(rhs, depth, Some(&Type::bool()))?;
+                let after = self.flow_state();
+                self.merge_flow(before, after);
+                if lhs.conflicts_with(&Type::bool()) || rhs.conflicts_with(&Type::bool()) {
+                    return Err(
+                        TypeErrors::OperationIsNotSupported(operator, vec![lhs, rhs]).into(),
+                    );
+                }
+                Ok(Type::bool())
+            }
+            // The amount of a shift may be of any integer type, independent of the shifted value.
+            TokenKind::ShiftLeft | TokenKind::ShiftRight => {
+                let amount = match &rhs.kind {
+                    ExpressionKind::Number(value, _) => value.parse::<i128>().ok(),
+                    _ => Self::untyped_literal(&rhs).map(|(_, amount)| amount),
+                };
+                let rhs = self.solve_expression_type(rhs, depth, None)?;
+                if lhs.is_error() || rhs.is_error() {
+                    return Ok(Type::error());
+                }
+                if !lhs.is_integer() || !rhs.is_integer() {
+                    return Err(
+                        TypeErrors::OperationIsNotSupported(operator, vec![lhs, rhs]).into(),
+                    );
+                }
+                if let Some(amount) = amount
+                    && !usize::try_from(amount).is_ok_and(|amount| amount < lhs.size)
+                {
+                    return Err(TypeErrors::ShiftOutOfRange(amount.to_string(), lhs).into());
+                }
+                Ok(lhs)
+            }
+            _ => {
+                let rhs = self.solve_expression_type(rhs, depth, Some(&lhs))?;
+                Ok(Self::operation_type(lhs, operator, rhs)?)
+            }
+        }
+    }
+
+    /// Type of an arithmetic, comparison or range operation on solved operands.
+    fn operation_type(lhs: Type, operator: TokenKind, rhs: Type) -> Result<Type, TypeErrors> {
+        use crate::event_script::token::TokenKind::{
+            Ampersand, Caret, DotDot, DotDotEquals, Equals, Greater, GreaterEquals, Less,
+            LessEquals, Minus, NotEquals, Percent, Pipe, Plus, Slash, Star,
+        };
+        if lhs.is_error() || rhs.is_error() {
+            return Ok(Type::error());
+        }
+        if lhs != rhs {
+            return Err(TypeErrors::OperationIsNotSupported(
+                operator,
+                vec![lhs, rhs],
+            ));
+        }
+        match operator {
+            Equals | NotEquals => Ok(Type::bool()),
+            Less | LessEquals | Greater | GreaterEquals if lhs.is_numeric() => Ok(Type::bool()),
+            Plus | Minus | Star | Slash | Percent if lhs.is_numeric() => Ok(lhs),
+            Plus if lhs == Type::string() => Ok(lhs),
+            Ampersand | Pipe | Caret if lhs.is_integer() => Ok(lhs),
+            DotDot | DotDotEquals if lhs.is_integer() => Ok(Type::range(lhs)),
+            _ => Err(TypeErrors::OperationIsNotSupported(
+                operator,
+                vec![lhs, rhs],
+            )),
+        }
+    }
+
     pub(super) fn initialize(&mut self) {
         self.add_type(&"i8", 8, 0).unwrap();
         self.add_type(&"i16", 16, 0).unwrap();
@@ -261,13 +1530,18 @@ impl TypeChecker {
 
         self.add_type(&"f32", 32, 0).unwrap();
         self.add_type(&"f64", 64, 0).unwrap();
+
+        self.add_type(&"bool", 1, 0).unwrap();
+        self.add_type(&"string", 0, 0).unwrap();
     }
 
-    /// .
+    /// Declares a type named `name` that is `size` bits wide in the scope at
+    /// `depth`, where annotations resolve it by name.
     ///
     /// # Errors
     ///
-    /// This function will return an error if .
+    /// Returns an error if a symbol with the same name is declared in a scope
+    /// nested inside that one.
     pub fn add_type<S: ToString>(
         &mut self,
         name: &S,
@@ -279,7 +1553,7 @@ impl TypeChecker {
             .iter()
             .any(|(key, _)| key.name == name.to_string() && key.depth > depth)
         {
-            return Err(TypeErrors::TypeAlreadyExists());
+            return Err(TypeErrors::TypeAlreadyExists(name.to_string()));
         }
         self.symbol_table.insert(
             Key::new(name, depth),
@@ -288,29 +1562,57 @@ impl TypeChecker {
         Ok(())
     }
 
-    /// .
+    /// Declares a variable in the scope at `depth`. `type_` is `None` until the
+    /// first assignment infers it and `assigned` tracks whether it holds a value.
     ///
     /// # Errors
     ///
-    /// This function will return an error if .
+    /// Returns an error if a symbol with the same name is already declared in
+    /// that scope or in one nested inside it.
     pub fn add_variable<S: ToString>(
         &mut self,
         name: &S,
         is_const: bool,
         mutable: bool,
         type_: Option<Type>,
+        assigned: bool,
         depth: u16,
     ) -> Result<(), TypeErrors> {
         if self
             .symbol_table
             .iter()
-            .any(|(key, _)| key.name == name.to_string() && key.depth > depth)
+            .any(|(key, _)| key.name == name.to_string() && key.depth >= depth)
         {
-            return Err(TypeErrors::VariableAlreadyExists());
+            return Err(TypeErrors::VariableAlreadyExists(name.to_string()));
         }
         self.symbol_table.insert(
             Key::new(name, depth),
-            SymbolType::Variable(Variable::new(name, is_const, mutable, type_)),
+            SymbolType::Variable(Variable::new(name, is_const, mutable, type_, assigned)),
+        );
+        Ok(())
+    }
+
+    /// Declares a function taking `params` and returning `return_type` in the
+    /// scope at `depth`, where calls resolve it by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a function with the same name is already declared
+    /// in that scope.
+    pub fn add_function<S: ToString>(
+        &mut self,
+        name: &S,
+        return_type: Type,
+        params: Vec<Type>,
+        depth: u16,
+    ) -> Result<(), TypeErrors> {
+        let key = Key::new(name, depth);
+        if matches!(self.symbol_table.get(&key), Some(SymbolType::Function(_))) {
+            return Err(TypeErrors::FunctionAlreadyExists(name.to_string()));
+        }
+        self.symbol_table.insert(
+            key,
+            SymbolType::Function(Function::new(name, return_type, params)),
         );
         Ok(())
     }
@@ -0,0 +1,323 @@
+use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::event_script::{
+    ast::Statement, bytecode::Chunk, interpreter::RuntimeErrors, token::TokenKind,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    Integer(i128, IntegerType),
+    Float(f64),
+    String(String),
+    // `start..end`, or `start..=end` when the flag is set, over integers of one type.
+    Range(i128, i128, bool, IntegerType),
+    Function(Rc<Callable>),
+}
+
+/// The type of an integer value, which bounds the results of its arithmetic.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IntegerType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+impl IntegerType {
+    /// The integer type called `name` in scripts, `None` if `name` isn't one.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "i8" => Some(IntegerType::I8),
+            "i16" => Some(IntegerType::I16),
+            "i32" => Some(IntegerType::I32),
+            "i64" => Some(IntegerType::I64),
+            "u8" => Some(IntegerType::U8),
+            "u16" => Some(IntegerType::U16),
+            "u32" => Some(IntegerType::U32),
+            "u64" => Some(IntegerType::U64),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            IntegerType::I8 => "i8",
+            IntegerType::I16 => "i16",
+            IntegerType::I32 => "i32",
+            IntegerType::I64 => "i64",
+            IntegerType::U8 => "u8",
+            IntegerType::U16 => "u16",
+            IntegerType::U32 => "u32",
+            IntegerType::U64 => "u64",
+        }
+    }
+
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        match self {
+            IntegerType::I8 | IntegerType::U8 => 8,
+            IntegerType::I16 | IntegerType::U16 => 16,
+            IntegerType::I32 | IntegerType::U32 => 32,
+            IntegerType::I64 | IntegerType::U64 => 64,
+        }
+    }
+
+    #[must_use]
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            IntegerType::I8 | IntegerType::I16 | IntegerType::I32 | IntegerType::I64
+        )
+    }
+
+    /// Smallest and largest value of the type.
+    #[must_use]
+    pub fn bounds(self) -> (i128, i128) {
+        if self.is_signed() {
+            let max = (1 << (self.bits() - 1)) - 1;
+            (-max - 1, max)
+        } else {
+            (0, (1 << self.bits()) - 1)
+        }
+    }
+
+    /// Keeps the low bits of `value` that fit the width of the type, the top
+    /// one being the sign of signed types.
+    #[must_use]
+    pub fn wrap(self, value: i128) -> i128 {
+        let unused = 128 - self.bits();
+        if self.is_signed() {
+            (value << unused) >> unused
+        } else {
+            value & ((1 << self.bits()) - 1)
+        }
+    }
+}
+
+/// A function as seen by one of the execution backends.
+#[derive(Debug, PartialEq)]
+pub struct Callable {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: FunctionBody,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FunctionBody {
+    Ast(Vec<Statement>),
+    Bytecode(Rc<Chunk>),
+}
+
+impl Value {
+    /// The value of an integer literal from its decimal digits and suffix,
+    /// negated when it is the operand of a unary minus. Literals without a
+    /// suffix are `i32`, like in the type checker, which writes the type it
+    /// inferred for them into their suffix. `None` if the type can't hold the value.
+    #[must_use]
+    pub fn integer_literal(digits: &str, suffix: Option<&str>, negative: bool) -> Option<Value> {
+        let type_ = suffix.map_or(Some(IntegerType::I32), IntegerType::from_name)?;
+        let value: i128 = digits.parse().ok()?;
+        Self::integer(if negative { -value } else { value }, type_).ok()
+    }
+
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "unit",
+            Value::Bool(_) => "bool",
+            Value::Integer(_, type_) => type_.name(),
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Range(..) => "range",
+            Value::Function(_) => "function",
+        }
+    }
+
+    /// Applies a prefix operator to the value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operator is not defined for the value's type.
+    pub fn unary(&self, operator: &TokenKind) -> Result<Value, RuntimeErrors> {
+        match (operator, self) {
+            (TokenKind::Minus, Value::Integer(value, type_)) => Self::integer(-value, *type_),
+            (TokenKind::Minus, Value::Float(value)) => Ok(Value::Float(-value)),
+            (TokenKind::Tilde, Value::Integer(value, type_)) => {
+                Ok(Value::Integer(type_.wrap(!value), *type_))
+            }
+            (TokenKind::PlusPlus, Value::Integer(value, type_)) => Self::integer(value + 1, *type_),
+            (TokenKind::MinusMinus, Value::Integer(value, type_)) => {
+                Self::integer(value - 1, *type_)
+            }
+            (TokenKind::PlusPlus, Value::Float(value)) => Ok(Value::Float(value + 1.0)),
+            (TokenKind::MinusMinus, Value::Float(value)) => Ok(Value::Float(value - 1.0)),
+            (TokenKind::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+            _ => Err(RuntimeErrors::UnsupportedUnaryOperation(
+                operator.clone(),
+                self.type_name(),
+            )),
+        }
+    }
+
+    /// Applies a non short-circuiting binary operator to two values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operator is not defined for the operand types,
+    /// if an integer result doesn't fit the type of the operands, or on
+    /// division by zero.
+    pub fn binary(&self, operator: &TokenKind, other: &Value) -> Result<Value, RuntimeErrors> {
+        use TokenKind::{
+            Ampersand, Caret, DotDot, DotDotEquals, Equals, Greater, GreaterEquals, Less,
+            LessEquals, Minus, NotEquals, Percent, Pipe, Plus, ShiftLeft, ShiftRight, Slash, Star,
+        };
+        match (self, other) {
+            // Both operands have the same type, except the amount of a shift.
+            (Value::Integer(lhs, type_), Value::Integer(rhs, rhs_type))
+                if type_ == rhs_type || matches!(operator, ShiftLeft | ShiftRight) =>
+            {
+                let (lhs, rhs, type_) = (*lhs, *rhs, *type_);
+                let result = match operator {
+                    Plus => lhs.checked_add(rhs),
+                    Minus => lhs.checked_sub(rhs),
+                    Star => lhs.checked_mul(rhs),
+                    Slash | Percent if rhs == 0 => return Err(RuntimeErrors::DivisionByZero),
+                    Slash => lhs.checked_div(rhs),
+                    Percent => lhs.checked_rem(rhs),
+                    Ampersand => Some(lhs & rhs),
+                    Pipe => Some(lhs | rhs),
+                    Caret => Some(lhs ^ rhs),
+                    // Shifting by a negative amount or by the width or more overflows,
+                    // bits shifted out of the width are dropped.
+                    ShiftLeft => Self::shift_amount(rhs, type_).map(|rhs| type_.wrap(lhs << rhs)),
+                    ShiftRight => Self::shift_amount(rhs, type_).map(|rhs| lhs >> rhs),
+                    Less => return Ok(Value::Bool(lhs < rhs)),
+                    LessEquals => return Ok(Value::Bool(lhs <= rhs)),
+                    Greater => return Ok(Value::Bool(lhs > rhs)),
+                    GreaterEquals => return Ok(Value::Bool(lhs >= rhs)),
+                    Equals => return Ok(Value::Bool(lhs == rhs)),
+                    NotEquals => return Ok(Value::Bool(lhs != rhs)),
+                    DotDot => return Ok(Value::Range(lhs, rhs, false, type_)),
+                    DotDotEquals => return Ok(Value::Range(lhs, rhs, true, type_)),
+                    _ => return Err(self.unsupported(operator, other)),
+                };
+                result.map_or(Err(RuntimeErrors::IntegerOverflow), |result| {
+                    Self::integer(result, type_)
+                })
+            }
+            (Value::Float(lhs), Value::Float(rhs)) => Ok(match operator {
+                Plus => Value::Float(lhs + rhs),
+                Minus => Value::Float(lhs - rhs),
+                Star => Value::Float(lhs * rhs),
+                Slash => Value::Float(lhs / rhs),
+                Percent => Value::Float(lhs % rhs),
+                Less => Value::Bool(lhs < rhs),
+                LessEquals => Value::Bool(lhs <= rhs),
+                Greater => Value::Bool(lhs > rhs),
+                GreaterEquals => Value::Bool(lhs >= rhs),
+                Equals => Value::Bool(lhs == rhs),
+                NotEquals => Value::Bool(lhs != rhs),
+                _ => return Err(self.unsupported(operator, other)),
+            }),
+            (Value::String(lhs), Value::String(rhs)) => Ok(match operator {
+                Plus => Value::String(format!("{lhs}{rhs}")),
+                Equals => Value::Bool(lhs == rhs),
+                NotEquals => Value::Bool(lhs != rhs),
+                _ => return Err(self.unsupported(operator, other)),
+            }),
+            (Value::Bool(lhs), Value::Bool(rhs)) => Ok(match operator {
+                Equals => Value::Bool(lhs == rhs),
+                NotEquals => Value::Bool(lhs != rhs),
+                _ => return Err(self.unsupported(operator, other)),
+            }),
+            _ => Err(self.unsupported(operator, other)),
+        }
+    }
+
+    /// Returns the truth value of a `bool`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not a `bool`.
+    pub fn as_bool(&self) -> Result<bool, RuntimeErrors> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            _ => Err(RuntimeErrors::ExpectedBool(self.type_name())),
+        }
+    }
+
+    /// Splits an iterable value into its first item and the remaining iterable,
+    /// or returns `None` once it is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value can't be iterated over.
+    pub fn next_item(&self) -> Result<Option<(Value, Value)>, RuntimeErrors> {
+        match self {
+            Value::Range(start, end, inclusive, type_) => {
+                if start < end {
+                    Ok(Some((
+                        Value::Integer(*start, *type_),
+                        Value::Range(start + 1, *end, *inclusive, *type_),
+                    )))
+                } else if *inclusive && start == end {
+                    Ok(Some((
+                        Value::Integer(*start, *type_),
+                        Value::Range(*start, *end, false, *type_),
+                    )))
+                } else {
+                    Ok(None)
+                }
+            }
+            _ => Err(RuntimeErrors::NotIterable(self.type_name())),
+        }
+    }
+
+    /// An integer of type `type_`, which overflows outside of the bounds of the type.
+    fn integer(value: i128, type_: IntegerType) -> Result<Value, RuntimeErrors> {
+        let (min, max) = type_.bounds();
+        if (min..=max).contains(&value) {
+            Ok(Value::Integer(value, type_))
+        } else {
+            Err(RuntimeErrors::IntegerOverflow)
+        }
+    }
+
+    fn shift_amount(amount: i128, type_: IntegerType) -> Option<u32> {
+        u32::try_from(amount)
+            .ok()
+            .filter(|amount| *amount < type_.bits())
+    }
+
+    fn unsupported(&self, operator: &TokenKind, other: &Value) -> RuntimeErrors {
+        RuntimeErrors::UnsupportedBinaryOperation(
+            operator.clone(),
+            self.type_name(),
+            other.type_name(),
+        )
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Integer(value, _) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::String(value) => write!(f, "{value}"),
+            Value::Range(start, end, false, _) => write!(f, "{start}..{end}"),
+            Value::Range(start, end, true, _) => write!(f, "{start}..={end}"),
+            Value::Function(function) => write!(f, "<fn {}>", function.name),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::rc::Rc;
+
+use crate::event_script::{
+    bytecode::{Chunk, OpCode},
+    interpreter::{MAX_CALL_DEPTH, RuntimeErrors},
+    value::{FunctionBody, Value},
+};
+
+struct CallFrame {
+    chunk: Rc<Chunk>,
+    ip: usize,
+    base: usize,
+}
+
+pub struct Vm {
+    script: Rc<Chunk>,
+    frames: Vec<CallFrame>,
+    stack: Vec<Value>,
+    locals: Vec<Option<Value>>,
+}
+
+impl Vm {
+    #[must_use]
+    pub fn new(chunk: Chunk) -> Self {
+        let locals = vec![None; chunk.locals_count];
+        Self {
+            script: Rc::new(chunk),
+            frames: Vec::new(),
+            stack: Vec::new(),
+            locals,
+        }
+    }
+
+    /// Executes the script chunk from the beginning.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first runtime error raised by an instruction.
+    pub fn run(&mut self) -> Result<(), RuntimeErrors> {
+        self.stack.clear();
+        self.locals.truncate(self.script.locals_count);
+        self.frames = vec![CallFrame {
+            chunk: self.script.clone(),
+            ip: 0,
+            base: 0,
+        }];
+
+        while let Some(frame) = self.frames.last_mut() {
+            let Some(op) = frame.chunk.code.get(frame.ip).cloned() else {
+                break;
+            };
+            frame.ip += 1;
+            let base = frame.base;
+            match op {
+                OpCode::Constant(index) => {
+                    let value = self.frame().chunk.constants[index as usize].clone();
+                    self.stack.push(value);
+                }
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::GetLocal(slot) => self.push_local(base + slot as usize)?,
+                OpCode::GetGlobal(slot) => self.push_local(slot as usize)?,
+                OpCode::SetGlobal(slot) => {
+                    self.locals[slot as usize] = self.stack.last().cloned();
+                }
+                OpCode::SetLocal(slot) => {
+                    self.locals[base + slot as usize] = self.stack.last().cloned();
+                }
+                OpCode::ClearLocal(slot) => self.locals[base + slot as usize] = None,
+                OpCode::Unit => self.stack.push(Value::Unit),
+                OpCode::Unary(operator) => {
+                    let value = self.pop().unary(&operator)?;
+                    self.stack.push(value);
+                }
+                OpCode::Binary(operator) => {
+                    let rhs = self.pop();
+                    let lhs = self.pop();
+                    self.stack.push(lhs.binary(&operator, &rhs)?);
+                }
+                OpCode::CheckBool => {
+                    self.peek().as_bool()?;
+                }
+                OpCode::Jump(target) => self.jump(target),
+                OpCode::JumpIfFalse(target) => {
+                    if !self.peek().as_bool()? {
+                        self.jump(target);
+                    }
+                }
+                OpCode::JumpIfTrue(target) => {
+                    if self.peek().as_bool()? {
+                        self.jump(target);
+                    }
+                }
+                OpCode::IterNext(iterator, variable, exit) => {
+                    let iterator = base + iterator as usize;
+                    let current = self.locals[iterator].take().unwrap_or(Value::Unit);
+                    if let Some((item, rest)) = current.next_item()? {
+                        self.locals[iterator] = Some(rest);
+                        self.locals[base + variable as usize] = Some(item);
+                    } else {
+                        self.jump(exit);
+                    }
+                }
+                OpCode::Call(count) => self.call(count as usize)?,
+                OpCode::Return => {
+                    if self.frames.len() == 1 {
+                        break;
+                    }
+                    let value = self.pop();
+                    if let Some(frame) = self.frames.pop() {
+                        self.locals.truncate(frame.base);
+                    }
+                    self.stack.push(value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the value of a variable declared at the top level of the script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable is not declared or not initialized.
+    pub fn get_variable(&self, name: &str) -> Result<&Value, RuntimeErrors> {
+        let slot = self
+            .script
+            .globals
+            .get(name)
+            .ok_or(RuntimeErrors::VariableDoesntExist(name.to_string()))?;
+        self.locals[*slot as usize]
+            .as_ref()
+            .ok_or(RuntimeErrors::UninitializedVariable(name.to_string()))
+    }
+
+    fn call(&mut self, count: usize) -> Result<(), RuntimeErrors> {
+        let callee_index = self.stack.len() - count - 1;
+        let Value::Function(function) = &self.stack[callee_index] else {
+            return Err(RuntimeErrors::NotCallable(
+                self.stack[callee_index].type_name(),
+            ));
+        };
+        let function = function.clone();
+        let FunctionBody::Bytecode(chunk) = &function.body else {
+            return Err(RuntimeErrors::NotCallable("function"));
+        };
+        if function.params.len() != count {
+            return Err(RuntimeErrors::ArgumentCountMismatch(
+                function.name.clone(),
+                function.params.len(),
+                count,
+            ));
+        }
+        // The script itself occupies the first frame.
+        if self.frames.len() > MAX_CALL_DEPTH {
+            return Err(RuntimeErrors::StackOverflow);
+        }
+
+        let base = self.locals.len();
+        self.locals.resize(base + chunk.locals_count, None);
+        for (offset, argument) in self.stack.drain(callee_index + 1..).enumerate() {
+            self.locals[base + offset] = Some(argument);
+        }
+        self.stack.pop();
+        self.frames.push(CallFrame {
+            chunk: chunk.clone(),
+            ip: 0,
+            base,
+        });
+        Ok(())
+    }
+
+    fn push_local(&mut self, index: usize) -> Result<(), RuntimeErrors> {
+        let value = self.locals[index]
+            .clone()
+            .ok_or_else(|| RuntimeErrors::UninitializedVariable(self.name_of(index)))?;
+        self.stack.push(value);
+        Ok(())
+    }
+
+    fn name_of(&self, index: usize) -> String {
+        self.script
+            .globals
+            .iter()
+            .find(|(_, slot)| **slot as usize == index)
+            .map_or_else(|| format!("#{index}"), |(name, _)| name.clone())
+    }
+
+    fn frame(&self) -> &CallFrame {
+        &self.frames[self.frames.len() - 1]
+    }
+
+    fn jump(&mut self, target: u32) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.ip = target as usize;
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap_or(Value::Unit)
+    }
+
+    fn peek(&self) -> &Value {
+        self.stack.last().unwrap_or(&Value::Unit)
+    }
+}
@@ -1,24 +1,44 @@
 // #![warn(clippy::pedantic)]
 // #![allow(clippy::match_wildcard_for_single_variants)]
 // #![allow(clippy::match_same_arms)]
-use crate::event_script::{
+use event_script::event_script::{
+    diagnostic::{Diagnostic, SourceMap},
+    interpreter::Interpreter,
     parser::Parser,
-    symbol_table::{SymbolTable, SymbolType},
     tokenizer::Lexer,
-    type_system::{Type, TypeChecker},
+    type_system::TypeChecker,
 };
 
-pub mod event_script;
-
 fn main() {
     let code = "let mut a = 5; { let b = 5 + a; } let c = a + a; let d = 7; ";
-    let tokens = Lexer::tokenize(code.to_string()).unwrap();
-    // println!("{:?}", tokens);
-    let stmt = Parser::parse(tokens);
-    // println!("{:?}", stmt);
-    // let res = TypeChecker::check(stmt.unwrap());
-    let mut table = SymbolTable::new();
-    table.insert_symbol(SymbolType::Type(Type::new(&"a", 8)), 2);
-    table.insert_symbol(SymbolType::Type(Type::new(&"a", 8)), 1);
-    println!("{table:?}");
+    let mut files = SourceMap::new();
+    let file = files.add("main.es", code.to_string());
+    let tokens = match Lexer::tokenize_file(code.to_string(), file) {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            eprintln!("{}", Diagnostic::from(&error).render(&files));
+            std::process::exit(1);
+        }
+    };
+    let (statements, errors) = Parser::parse_recovering(tokens);
+    if !errors.is_empty() {
+        for error in &errors {
+            eprintln!("{}", Diagnostic::from(error).render(&files));
+        }
+        std::process::exit(1);
+    }
+    // The backends run literals at the types the checker wrote back into the tree.
+    let statements = match TypeChecker::check(statements) {
+        Ok(statements) => statements,
+        Err(error) => {
+            eprintln!("{}", Diagnostic::from(&error).render(&files));
+            std::process::exit(1);
+        }
+    };
+    let mut interpreter = Interpreter::new();
+    if let Err(error) = interpreter.run(&statements) {
+        eprintln!("runtime error: {error}");
+        std::process::exit(1);
+    }
+    println!("{:?}", interpreter.get_variable("c"));
 }
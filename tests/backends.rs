@@ -0,0 +1,431 @@
+use event_script::event_script::{
+    compiler::Compiler,
+    interpreter::{Interpreter, RuntimeErrors},
+    parser::Parser,
+    tokenizer::Lexer,
+    type_system::TypeChecker,
+    value::{IntegerType, Value},
+    vm::Vm,
+};
+
+fn int(value: i128) -> Value {
+    Value::Integer(value, IntegerType::I32)
+}
+
+/// Runs a script on both backends and returns the values of the named variables.
+/// Scripts that pass the type checker run with the literal types it inferred,
+/// the others as parsed so both backends can be compared on runtime errors.
+fn run_both(code: &str, names: &[&str]) -> Vec<Result<Value, String>> {
+    let tokens = Lexer::tokenize(code.to_string()).unwrap();
+    let statements = Parser::parse(tokens).unwrap();
+    let statements = TypeChecker::check(statements.clone()).unwrap_or(statements);
+
+    let mut interpreter = Interpreter::new();
+    let interpreted = interpreter.run(&statements).map_err(|e| e.to_string());
+
+    let mut vm = Vm::new(Compiler::compile(&statements).unwrap());
+    let executed = vm.run().map_err(|e| e.to_string());
+    assert_eq!(interpreted, executed, "{code}");
+
+    names
+        .iter()
+        .map(|name| {
+            let expected = interpreter.get_variable(name).cloned();
+            let actual = vm.get_variable(name).cloned();
+            assert_eq!(expected, actual, "{code}: {name}");
+            expected.map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+/// Runs a script that passes the type checker on both backends and returns
+/// the error both of them stop with.
+fn run_error(code: &str) -> RuntimeErrors {
+    let tokens = Lexer::tokenize(code.to_string()).unwrap();
+    let statements = TypeChecker::check(Parser::parse(tokens).unwrap()).unwrap();
+    let interpreted = Interpreter::new().run(&statements).unwrap_err();
+    let executed = Vm::new(Compiler::compile(&statements).unwrap())
+        .run()
+        .unwrap_err();
+    assert_eq!(interpreted, executed, "{code}");
+    interpreted
+}
+
+#[test]
+fn arithmetic() {
+    let values = run_both(
+        "let a = 5; let b = a * 3 - 4 / 2; let c = (a + b) % 7; let d = -a;",
+        &["a", "b", "c", "d"],
+    );
+    assert_eq!(
+        values,
+        vec![Ok(int(5)), Ok(int(13)), Ok(int(4)), Ok(int(-5)),]
+    );
+}
+
+#[test]
+fn strings_and_comparisons() {
+    let values = run_both(
+        r#"let s = "ab" + "cd"; let lt = 1 < 2; let eq = s == "abcd";"#,
+        &["s", "lt", "eq"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(Value::String("abcd".to_string())),
+            Ok(Value::Bool(true)),
+            Ok(Value::Bool(true)),
+        ]
+    );
+}
+
+#[test]
+fn blocks_shadow_and_restore() {
+    let values = run_both(
+        "let a = 1; let b = 0; { let a = 10; let c = a + 1; } let d = a;",
+        &["a", "d", "c"],
+    );
+    assert_eq!(values[0], Ok(int(1)));
+    assert_eq!(values[1], Ok(int(1)));
+    assert!(values[2].is_err());
+}
+
+#[test]
+fn redeclared_globals_are_shared() {
+    // The checker rejects these, both backends still run them the same way.
+    let values = run_both(
+        "let a = 1; fn f(): i32 { return a; } let a = 2; let r = f();",
+        &["a", "r"],
+    );
+    assert_eq!(values, vec![Ok(int(2)), Ok(int(2))]);
+
+    let values = run_both(
+        "let a = 1; fn f(): i32 { return a; } let a = \"s\"; let r = f();",
+        &["r"],
+    );
+    assert_eq!(values, vec![Ok(Value::String("s".to_string()))]);
+
+    // Declaring without a value clears the shared binding before the call reads it.
+    run_both(
+        "let mut a = 1; fn f(): i32 { a += 1; return a; } let a; a = f() * 10;",
+        &[],
+    );
+}
+
+#[test]
+fn short_circuit() {
+    let values = run_both("let f = 2 < 1; let a = f && 1 / 0 == 0;", &["a"]);
+    assert_eq!(values, vec![Ok(Value::Bool(false))]);
+}
+
+#[test]
+fn runtime_errors_match() {
+    run_both("let a = 1 / 0;", &[]);
+    run_both(r#"let a = "a" - 1;"#, &[]);
+    run_both("let a: i32; let b = a;", &[]);
+}
+
+#[test]
+fn functions() {
+    let values = run_both(
+        "let base = 10; \
+         fn add(a: i32, b: i32): i32 { let sum = a + b; return sum + base; } \
+         fn twice(x: i32): i32 { return add(x, x); } \
+         fn nothing() { return; } \
+         let a = add(1, 2); let b = twice(add(0, 1)); let c = nothing();",
+        &["a", "b", "c"],
+    );
+    assert_eq!(values, vec![Ok(int(13)), Ok(int(32)), Ok(Value::Unit)]);
+}
+
+#[test]
+fn call_errors_match() {
+    run_both("fn f(a: i32) { } let a = f();", &[]);
+    run_both("let a = 1; let b = a(2);", &[]);
+    run_both("fn f(): i32 { return f(); } let a = f();", &[]);
+}
+
+#[test]
+fn conditionals() {
+    let values = run_both(
+        "fn sign(x: i32): i32 { if x < 0 { return -1; } else if x == 0 { return 0; } else { return 1; } } \
+         fn fact(n: i32): i32 { if n < 2 { return 1; } return n * fact(n - 1); } \
+         let a = sign(-5); let b = sign(0); let c = sign(7); let d = fact(10); \
+         let e = if a < b { let t = 2; t * 2 } else { 0 }; \
+         let f = if c == 0 { 1 } else if c == 1 { 2 } else { 3 };",
+        &["a", "b", "c", "d", "e", "f"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(int(-1)),
+            Ok(int(0)),
+            Ok(int(1)),
+            Ok(int(3_628_800)),
+            Ok(int(4)),
+            Ok(int(2)),
+        ]
+    );
+}
+
+#[test]
+fn nested_if_expressions() {
+    let values = run_both(
+        "fn pick(a: bool, b: bool): i32 { return if a { if b { 1 } else { 2 } } else { 3 }; } \
+         let x = pick(true, true); let y = pick(true, false); let z = pick(false, true); \
+         let mut n = 0; let w = if x == 1 { if n == 0 { n = 5; } n + 1 } else { 0 };",
+        &["x", "y", "z", "n", "w"],
+    );
+    assert_eq!(
+        values,
+        vec![Ok(int(1)), Ok(int(2)), Ok(int(3)), Ok(int(5)), Ok(int(6)),]
+    );
+}
+
+#[test]
+fn condition_errors_match() {
+    run_both("if 1 { }", &[]);
+    run_both("let a = if 1 { 1 } else { 2 };", &[]);
+}
+
+#[test]
+fn loops() {
+    let values = run_both(
+        "fn first(limit: i32): i32 { while 0 < limit { if limit == 1 { return 1; } break; } return 0; } \
+         let a = first(1); let b = first(2); let c = first(0); \
+         let d = 0; while d < 1 { let skip = d == 1; if skip { continue; } while 1 < 2 { break; } break; }",
+        &["a", "b", "c", "d"],
+    );
+    assert_eq!(
+        values,
+        vec![Ok(int(1)), Ok(int(0)), Ok(int(0)), Ok(int(0)),]
+    );
+}
+
+#[test]
+fn for_loops() {
+    let values = run_both(
+        "fn find(n: i32, target: i32): i32 { for i in 0..n { if i == target { return i; } } return -1; } \
+         fn last(n: i32): i32 { for i in 0..=n { if i < n { continue; } return i; } return -1; } \
+         fn first_even(from: i32): i32 { for i in from..from + 10 { if i % 2 == 0 { return i; } } return -1; } \
+         fn breaks(): i32 { for i in 0..10 { break; } return 7; } \
+         let a = find(10, 3); let b = find(3, 3); let c = last(4); let d = last(-1); \
+         let e = first_even(5); let f = breaks(); let r = 1..=3;",
+        &["a", "b", "c", "d", "e", "f", "r"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(int(3)),
+            Ok(int(-1)),
+            Ok(int(4)),
+            Ok(int(-1)),
+            Ok(int(6)),
+            Ok(int(7)),
+            Ok(Value::Range(1, 3, true, IntegerType::I32)),
+        ]
+    );
+}
+
+#[test]
+fn iteration_errors_match() {
+    run_both("for i in 5 { }", &[]);
+}
+
+#[test]
+fn booleans() {
+    let values = run_both(
+        "let t = true; let f = false; let a = t && !f; let b = f == false; let c = !(1 < 2); \
+         let d = if t { 1 } else { 2 };",
+        &["t", "f", "a", "b", "c", "d"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(Value::Bool(true)),
+            Ok(Value::Bool(false)),
+            Ok(Value::Bool(true)),
+            Ok(Value::Bool(true)),
+            Ok(Value::Bool(false)),
+            Ok(int(1)),
+        ]
+    );
+}
+
+#[test]
+fn floats() {
+    let values = run_both("let a = 1.5 * 2.0; let b = 1e-3 < 0.5;", &["a", "b"]);
+    assert_eq!(values, vec![Ok(Value::Float(3.0)), Ok(Value::Bool(true))]);
+}
+
+#[test]
+fn assignments() {
+    let values = run_both(
+        "let a; let mut b = 1; a = 2; b = b + a; \
+         let c; let d; c = d = 3; \
+         fn bump(): i32 { b = b * 10; return b; } \
+         let e = bump(); \
+         let f = 0; { let mut f = 1; f = 5; }",
+        &["a", "b", "c", "d", "e", "f"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(int(2)),
+            Ok(int(30)),
+            Ok(int(3)),
+            Ok(int(3)),
+            Ok(int(30)),
+            Ok(int(0)),
+        ]
+    );
+}
+
+#[test]
+fn compound_assignments() {
+    let values = run_both(
+        "let mut a = 10; a += 5; a -= 3; a *= 4; a /= 6; a %= 5; \
+         let mut b = 1; let c = b++; let d = ++b; let e = b--; let f = --b; \
+         let mut g = 1.5; g += 1.0; g++; \
+         let mut h = 2; let i = (h += 3) * 2;",
+        &["a", "b", "c", "d", "e", "f", "g", "h", "i"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(int(3)),
+            Ok(int(1)),
+            Ok(int(1)),
+            Ok(int(3)),
+            Ok(int(3)),
+            Ok(int(1)),
+            Ok(Value::Float(3.5)),
+            Ok(int(5)),
+            Ok(int(10)),
+        ]
+    );
+}
+
+#[test]
+fn compound_assignment_errors_match() {
+    run_both("let mut a = 1; a /= 0;", &[]);
+    run_both("let mut a = true; a++;", &[]);
+}
+
+#[test]
+fn bitwise_operators() {
+    let values = run_both(
+        "let flags = 0b0101 | 0b1000; let masked = flags & ~0b0001; let toggled = flags ^ 0xf; \
+         let shifted = 1 << 4 >> 2;",
+        &["flags", "masked", "toggled", "shifted"],
+    );
+    assert_eq!(
+        values,
+        vec![Ok(int(13)), Ok(int(12)), Ok(int(2)), Ok(int(4)),]
+    );
+    run_both("let n = 64; let a = 1 << n;", &[]);
+    run_both("let n = -1; let a = 1 >> n;", &[]);
+}
+
+#[test]
+fn bitwise_operators_follow_the_width() {
+    let values = run_both(
+        "let a = ~0u8; let b: u16 = ~1; let c: u8 = 0xF0 << 2; let d: u8 = 255 >> 4; \
+         let e = 1 << 31; let f: i8 = 1 << 7; let g: u32 = ~0 >> 28; let h: i8 = -128 >> 7;",
+        &["a", "b", "c", "d", "e", "f", "g", "h"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(Value::Integer(255, IntegerType::U8)),
+            Ok(Value::Integer(65534, IntegerType::U16)),
+            Ok(Value::Integer(0xC0, IntegerType::U8)),
+            Ok(Value::Integer(15, IntegerType::U8)),
+            Ok(int(i32::MIN.into())),
+            Ok(Value::Integer(-128, IntegerType::I8)),
+            Ok(Value::Integer(15, IntegerType::U32)),
+            Ok(Value::Integer(-1, IntegerType::I8)),
+        ]
+    );
+    for code in [
+        "let a: u8 = 1; let n = 8; let b = a << n;",
+        "let a: u16 = 1; let n = 16; let b = a >> n;",
+    ] {
+        assert_eq!(run_error(code), RuntimeErrors::IntegerOverflow, "{code}");
+    }
+}
+
+#[test]
+fn full_width_integer_literals() {
+    let values = run_both(
+        "let a: u64 = 18446744073709551615; let b: u64 = 0xFFFF_FFFF_FFFF_FFFF; \
+         let c: i64 = -9223372036854775808; let d = a - b;",
+        &["a", "b", "c", "d"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(Value::Integer(u64::MAX.into(), IntegerType::U64)),
+            Ok(Value::Integer(u64::MAX.into(), IntegerType::U64)),
+            Ok(Value::Integer(i64::MIN.into(), IntegerType::I64)),
+            Ok(Value::Integer(0, IntegerType::U64)),
+        ]
+    );
+    run_both("let a: u64 = 18446744073709551615; let b = a + 1;", &[]);
+}
+
+#[test]
+fn literals_take_the_type_inferred_later() {
+    let programs = [
+        "fn f(x: u64): u64 { return x + 1; } let a; a = 3; let r = f(a);",
+        "let mut x; x = 5; let y: u8 = x; let r: u8 = y + 1u8;",
+        "let a; a = 3000000000; let r: u64 = a;",
+        "let mut a; a = 1; a = -2; let r: i8 = a * 3;",
+    ];
+    for code in programs {
+        let tokens = Lexer::tokenize(code.to_string()).unwrap();
+        assert!(
+            TypeChecker::check(Parser::parse(tokens).unwrap()).is_ok(),
+            "{code}"
+        );
+    }
+    let values: Vec<_> = programs
+        .iter()
+        .map(|code| run_both(code, &["r"]).remove(0))
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            Ok(Value::Integer(4, IntegerType::U64)),
+            Ok(Value::Integer(6, IntegerType::U8)),
+            Ok(Value::Integer(3_000_000_000, IntegerType::U64)),
+            Ok(Value::Integer(-6, IntegerType::I8)),
+        ]
+    );
+}
+
+#[test]
+fn integer_overflow_follows_the_type() {
+    let values = run_both(
+        "let a: u8 = 254; let b = a + 1; let c: i8 = -128; let d = c + 127; let e = 3000000000u32;",
+        &["b", "d", "e"],
+    );
+    assert_eq!(
+        values,
+        vec![
+            Ok(Value::Integer(255, IntegerType::U8)),
+            Ok(Value::Integer(-1, IntegerType::I8)),
+            Ok(Value::Integer(3_000_000_000, IntegerType::U32)),
+        ]
+    );
+    for code in [
+        "let a: u8 = 255; let b = a + 1;",
+        "let a: u8 = 0; let b = a - 1;",
+        "let mut a: u8 = 255; a++;",
+        "let a: i32 = 2147483647; let b = a + 1;",
+        "let a = 65536; let b = a * a;",
+        "let a: i32 = -2147483648; let b = a / -1;",
+    ] {
+        assert_eq!(run_error(code), RuntimeErrors::IntegerOverflow, "{code}");
+    }
+}
@@ -0,0 +1,196 @@
+use event_script::event_script::{
+    diagnostic::{Diagnostic, SourceMap},
+    parser::Parser,
+    span::Span,
+    tokenizer::Lexer,
+    type_system::TypeChecker,
+};
+
+/// Runs a script through every stage and renders the first error.
+fn render(code: &str) -> String {
+    let mut files = SourceMap::new();
+    let file = files.add("main.es", code.to_string());
+    let diagnostic = match Lexer::tokenize_file(code.to_string(), file) {
+        Err(error) => Diagnostic::from(&error),
+        Ok(tokens) => match Parser::parse(tokens) {
+            Err(error) => Diagnostic::from(&error),
+            Ok(statements) => {
+                let error = TypeChecker::check(statements).unwrap_err();
+                Diagnostic::from(&error)
+            }
+        },
+    };
+    diagnostic.render(&files)
+}
+
+/// Runs a script through every stage, recovering from syntax errors, and
+/// renders every error.
+fn render_all(code: &str) -> Vec<String> {
+    let mut files = SourceMap::new();
+    let file = files.add("main.es", code.to_string());
+    let tokens = Lexer::tokenize_file(code.to_string(), file).unwrap();
+    let (statements, errors) = Parser::parse_recovering(tokens);
+    let mut rendered: Vec<_> = errors
+        .iter()
+        .map(|error| Diagnostic::from(error).render(&files))
+        .collect();
+    if let Err(error) = TypeChecker::check(statements) {
+        rendered.push(Diagnostic::from(&error).render(&files));
+    }
+    rendered
+}
+
+#[test]
+fn tokenizer_errors() {
+    assert_eq!(
+        render("let a = 1;\nlet b = a # 2;"),
+        "\
+error[E0101]: unexpected character `#`
+ --> main.es:2:11
+  |
+2 | let b = a # 2;
+  |           ^ no token starts with this character
+"
+    );
+    assert_eq!(
+        render("let a = \"é\nb;"),
+        "\
+error[E0102]: unterminated string literal
+ --> main.es:1:9
+  |
+1 | let a = \"é
+  |         ^ string starts here
+  |
+  = help: add a closing `\"`
+"
+    );
+    assert_eq!(
+        render("let a = 10u8x;"),
+        "\
+error[E0103]: invalid suffix `u8x` for number literal
+ --> main.es:1:11
+  |
+1 | let a = 10u8x;
+  |           ^^^ invalid suffix
+  |
+  = help: integer literals can end in `i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32` or `u64`
+"
+    );
+}
+
+#[test]
+fn parser_errors() {
+    assert_eq!(
+        render("let a = 1 < 2 < 3;"),
+        "\
+error[E0208]: `<` cannot be chained
+ --> main.es:1:15
+  |
+1 | let a = 1 < 2 < 3;
+  |               ^ second comparison in a row
+  |
+  = help: use parentheses to group the comparisons
+"
+    );
+    assert_eq!(
+        render("let a = 1"),
+        "\
+error[E0206]: unexpected end of file
+ --> main.es:1:10
+  |
+1 | let a = 1
+  |          ^ unexpected token
+"
+    );
+}
+
+#[test]
+fn type_errors_point_at_the_innermost_node() {
+    assert_eq!(
+        render("let a: u8 = 1.5;"),
+        "\
+error[E0324]: mismatched types
+ --> main.es:1:13
+  |
+1 | let a: u8 = 1.5;
+  |             ^^^ expected `u8`, found `f32`
+"
+    );
+    assert_eq!(
+        render("let a = 1;\nlet b = a + true;"),
+        "\
+error[E0304]: cannot apply `+` to `i32` and `bool`
+ --> main.es:2:9
+  |
+2 | let b = a + true;
+  |         ^^^^^^^^ unsupported operation
+  |
+  = note: both operands must have the same type
+"
+    );
+    assert_eq!(
+        render("let a: u8 = 300;"),
+        "\
+error[E0322]: literal out of range for `u8`
+ --> main.es:1:13
+  |
+1 | let a: u8 = 300;
+  |             ^^^ `300` does not fit in `u8`
+  |
+  = note: `u8` ranges from 0 to 255
+"
+    );
+}
+
+#[test]
+fn secondary_labels_on_other_lines() {
+    assert_eq!(
+        render("let a = 1;\nlet b = 2;\n\na = 3;"),
+        "\
+error[E0328]: cannot assign twice to immutable variable `a`
+ --> main.es:4:1
+  |
+1 | let a = 1;
+  |     - declared here
+...
+4 | a = 3;
+  | ^ cannot assign twice
+  |
+  = help: make the variable mutable with `let mut a`
+"
+    );
+}
+
+#[test]
+fn diagnostics_without_a_location() {
+    let diagnostic = Diagnostic::error("E0203", &"unexpected end of input").with_note(&"note");
+    assert_eq!(
+        diagnostic.render(&SourceMap::new()),
+        "error[E0203]: unexpected end of input\n= note: note\n"
+    );
+
+    let mut files = SourceMap::new();
+    let file = files.add("main.es", "a\nb".to_string());
+    let spanned = Diagnostic::error("E0000", &"test").with_primary(Span::new(file, 3, 3), &"here");
+    assert_eq!(
+        spanned.render(&files),
+        "error[E0000]: test\n --> main.es:2:2\n  |\n2 | b\n  |  ^ here\n"
+    );
+}
+
+#[test]
+fn broken_function_header_is_one_error() {
+    let expected = "\
+error[E0206]: unexpected `{`
+ --> main.es:1:13
+  |
+1 | fn f(a: i32 { return a; }
+  |             ^ unexpected token
+";
+    assert_eq!(render_all("fn f(a: i32 { return a; }"), [expected]);
+    // Neither the body nor calls of the function are reported again.
+    let rendered =
+        render_all("fn f(a: i32 { if a < 0 { return 0; } return a; }\nlet b = f(1) + 1;");
+    assert_eq!(rendered.len(), 1);
+    assert!(rendered[0].starts_with("error[E0206]"));
+}
@@ -0,0 +1,91 @@
+use event_script::event_script::{
+    interpreter::{Interpreter, RuntimeErrors},
+    parser::Parser,
+    tokenizer::Lexer,
+    type_system::TypeChecker,
+    value::{IntegerType, Value},
+};
+
+fn int(value: i128) -> Value {
+    Value::Integer(value, IntegerType::I32)
+}
+
+/// Type-checks a script and runs it on the interpreter.
+fn run(code: &str) -> Result<Interpreter, RuntimeErrors> {
+    let tokens = Lexer::tokenize(code.to_string()).unwrap();
+    let statements = TypeChecker::check(Parser::parse(tokens).unwrap()).unwrap();
+    let mut interpreter = Interpreter::new();
+    interpreter.run(&statements)?;
+    Ok(interpreter)
+}
+
+#[test]
+fn variables_and_literals() {
+    let interpreter =
+        run(r#"let a = 5; let b = true; let c = "text"; let d = 3u8; let e = -7i64;"#).unwrap();
+    assert_eq!(interpreter.get_variable("a"), Ok(&int(5)));
+    assert_eq!(interpreter.get_variable("b"), Ok(&Value::Bool(true)));
+    assert_eq!(
+        interpreter.get_variable("c"),
+        Ok(&Value::String("text".to_string()))
+    );
+    assert_eq!(
+        interpreter.get_variable("d"),
+        Ok(&Value::Integer(3, IntegerType::U8))
+    );
+    assert_eq!(
+        interpreter.get_variable("e"),
+        Ok(&Value::Integer(-7, IntegerType::I64))
+    );
+}
+
+#[test]
+fn binary_unary_and_grouping() {
+    let interpreter =
+        run("let a = (1 + 2) * 3; let b = -a; let c = !(a == 9); let d = 7 % 4;").unwrap();
+    assert_eq!(interpreter.get_variable("a"), Ok(&int(9)));
+    assert_eq!(interpreter.get_variable("b"), Ok(&int(-9)));
+    assert_eq!(interpreter.get_variable("c"), Ok(&Value::Bool(false)));
+    assert_eq!(interpreter.get_variable("d"), Ok(&int(3)));
+}
+
+#[test]
+fn assignment_updates_the_declaring_scope() {
+    let interpreter = run("let mut a = 1; { let b = a + 1; a = b * 10; } let c = a;").unwrap();
+    assert_eq!(interpreter.get_variable("a"), Ok(&int(20)));
+    assert_eq!(interpreter.get_variable("c"), Ok(&int(20)));
+    assert_eq!(
+        interpreter.get_variable("b"),
+        Err(RuntimeErrors::VariableDoesntExist("b".to_string()))
+    );
+}
+
+#[test]
+fn literals_run_at_the_inferred_type() {
+    let interpreter = run("let a: u64 = 10000000000; let b: u8 = 200; let c = a + 1;").unwrap();
+    assert_eq!(
+        interpreter.get_variable("a"),
+        Ok(&Value::Integer(10_000_000_000, IntegerType::U64))
+    );
+    assert_eq!(
+        interpreter.get_variable("b"),
+        Ok(&Value::Integer(200, IntegerType::U8))
+    );
+    assert_eq!(
+        interpreter.get_variable("c"),
+        Ok(&Value::Integer(10_000_000_001, IntegerType::U64))
+    );
+}
+
+#[test]
+fn overflow_is_checked_at_the_value_type() {
+    assert_eq!(
+        run("let a: u8 = 200; let b = a + 100;").err(),
+        Some(RuntimeErrors::IntegerOverflow)
+    );
+    assert!(run("let a: i64 = 2147483647; let b = a + 1;").is_ok());
+    assert_eq!(
+        run("let a = 1; let b = a / 0;").err(),
+        Some(RuntimeErrors::DivisionByZero)
+    );
+}
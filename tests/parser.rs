@@ -0,0 +1,482 @@
+use event_script::event_script::{
+    ast::{Expression, ExpressionKind, Statement, StatementKind},
+    parser::{Parser, ParserErrors},
+    span::Span,
+    token::TokenKind,
+    tokenizer::Lexer,
+};
+
+fn parse(code: &str) -> Result<Vec<Statement>, ParserErrors> {
+    Parser::parse(Lexer::tokenize(code.to_string()).unwrap())
+}
+
+/// Parses a list of expression statements and returns the expressions.
+fn expressions(code: &str) -> Vec<ExpressionKind> {
+    parse(code)
+        .unwrap()
+        .into_iter()
+        .map(|statement| match statement.kind {
+            StatementKind::Expression(expression) => expression.into_kind(),
+            kind => panic!("{code}: expected an expression statement, got {kind:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn assignment_targets() {
+    let expressions = expressions("a = b = 3;");
+    let [ExpressionKind::Assignment(target, value)] = expressions.as_slice() else {
+        panic!("expected a single assignment, got {expressions:?}");
+    };
+    assert!(matches!(&target.kind, ExpressionKind::Symbol(token) if token.value == "a"));
+    let ExpressionKind::Assignment(inner_target, inner_value) = &value.kind else {
+        panic!("assignment must be right associative, got {value:?}");
+    };
+    assert!(matches!(&inner_target.kind, ExpressionKind::Symbol(token) if token.value == "b"));
+    assert!(matches!(&inner_value.kind, ExpressionKind::Number(value, None) if value == "3"));
+
+    assert!(matches!(
+        parse("1 = 2;"),
+        Err(ParserErrors::InvalidAssignmentTarget(_))
+    ));
+    assert!(matches!(
+        parse("a + b = 2;"),
+        Err(ParserErrors::InvalidAssignmentTarget(_))
+    ));
+    assert!(matches!(
+        parse("f() = 2;"),
+        Err(ParserErrors::InvalidAssignmentTarget(_))
+    ));
+}
+
+#[test]
+fn compound_assignments_and_increments() {
+    assert!(matches!(
+        expressions("a += b -= 2; ++a; a--;").as_slice(),
+        [
+            ExpressionKind::CompoundAssignment(_, TokenKind::Plus, value),
+            ExpressionKind::Increment(_, TokenKind::PlusPlus, true),
+            ExpressionKind::Increment(_, TokenKind::MinusMinus, false),
+        ] if matches!(value.kind, ExpressionKind::CompoundAssignment(_, TokenKind::Minus, _))
+    ));
+    assert!(matches!(
+        parse("1 += 2;"),
+        Err(ParserErrors::InvalidAssignmentTarget(_))
+    ));
+    assert!(matches!(
+        parse("++1;"),
+        Err(ParserErrors::InvalidAssignmentTarget(_))
+    ));
+    assert!(matches!(
+        parse("f()++;"),
+        Err(ParserErrors::InvalidAssignmentTarget(_))
+    ));
+}
+
+/// Renders an expression fully parenthesized, using the operators' source symbols.
+fn render(expression: &Expression) -> String {
+    let symbol = |kind: &TokenKind| match kind {
+        TokenKind::Plus => "+",
+        TokenKind::Minus => "-",
+        TokenKind::Star => "*",
+        TokenKind::Slash => "/",
+        TokenKind::Percent => "%",
+        TokenKind::Less => "<",
+        TokenKind::LessEquals => "<=",
+        TokenKind::Greater => ">",
+        TokenKind::GreaterEquals => ">=",
+        TokenKind::Equals => "==",
+        TokenKind::NotEquals => "!=",
+        TokenKind::And => "&&",
+        TokenKind::Or => "||",
+        TokenKind::DotDot => "..",
+        TokenKind::DotDotEquals => "..=",
+        TokenKind::Pipe => "|",
+        TokenKind::Caret => "^",
+        TokenKind::Ampersand => "&",
+        TokenKind::ShiftLeft => "<<",
+        TokenKind::ShiftRight => ">>",
+        TokenKind::PlusPlus => "++",
+        TokenKind::MinusMinus => "--",
+        _ => panic!("no symbol for {kind:?}"),
+    };
+    match &expression.kind {
+        ExpressionKind::Symbol(token) => token.value.clone(),
+        ExpressionKind::Number(value, _) => value.clone(),
+        ExpressionKind::Groupping(inner) => render(inner),
+        ExpressionKind::Unary(operator, operand) => {
+            format!("({}{})", operator.value, render(operand))
+        }
+        ExpressionKind::Binary(lhs, operator, rhs) => {
+            format!("({} {} {})", render(lhs), symbol(operator), render(rhs))
+        }
+        ExpressionKind::Assignment(target, value) => {
+            format!("({} = {})", render(target), render(value))
+        }
+        ExpressionKind::CompoundAssignment(target, operator, value) => {
+            format!(
+                "({} {}= {})",
+                render(target),
+                symbol(operator),
+                render(value)
+            )
+        }
+        ExpressionKind::Increment(target, operator, true) => {
+            format!("({}{})", symbol(operator), render(target))
+        }
+        ExpressionKind::Increment(target, operator, false) => {
+            format!("({}{})", render(target), symbol(operator))
+        }
+        ExpressionKind::FunctionCall(callee, arguments) => {
+            let arguments: Vec<_> = arguments.iter().map(render).collect();
+            format!("{}({})", render(callee), arguments.join(", "))
+        }
+        ExpressionKind::If(condition, body, otherwise) => {
+            format!(
+                "if {} {} else {}",
+                render(condition),
+                render(body),
+                render(otherwise)
+            )
+        }
+        ExpressionKind::Block(statements, tail) => {
+            let mut parts: Vec<_> = statements
+                .iter()
+                .map(|statement| match &statement.kind {
+                    StatementKind::Expression(expression) => format!("{};", render(expression)),
+                    kind => panic!("unexpected statement {kind:?}"),
+                })
+                .collect();
+            parts.extend(tail.iter().map(|tail| render(tail)));
+            if parts.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", parts.join(" "))
+            }
+        }
+        _ => panic!("unexpected expression {expression:?}"),
+    }
+}
+
+fn parse_rendered(code: &str) -> Result<String, String> {
+    match parse(code) {
+        Ok(statements) => match statements.as_slice() {
+            [
+                Statement {
+                    kind: StatementKind::Expression(expression),
+                    ..
+                },
+            ] => Ok(render(expression)),
+            _ => panic!("{code}: expected a single expression, got {statements:?}"),
+        },
+        Err(err) => Err(format!("{err:?}")),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Assoc {
+    Left,
+    Right,
+    None,
+}
+
+/// Binary operators from the loosest to the tightest level.
+const LEVELS: &[(Assoc, &[&str])] = &[
+    (Assoc::Right, &["=", "+=", "-=", "*=", "/=", "%="]),
+    (Assoc::None, &["..", "..="]),
+    (Assoc::Left, &["||"]),
+    (Assoc::Left, &["&&"]),
+    (Assoc::None, &["<", "<=", ">", ">=", "==", "!="]),
+    (Assoc::Left, &["|"]),
+    (Assoc::Left, &["^"]),
+    (Assoc::Left, &["&"]),
+    (Assoc::Left, &["<<", ">>"]),
+    (Assoc::Left, &["+", "-"]),
+    (Assoc::Left, &["*", "/", "%"]),
+];
+
+#[test]
+fn binary_operator_pairs() {
+    let is_assignment = |operator: &str| LEVELS[0].1.contains(&operator);
+    for (outer_level, (_, outer_operators)) in LEVELS.iter().enumerate() {
+        for (inner_level, (assoc, inner_operators)) in LEVELS.iter().enumerate() {
+            for first in *outer_operators {
+                for second in *inner_operators {
+                    let code = format!("a {first} b {second} c;");
+                    let left = || format!("((a {first} b) {second} c)");
+                    let right = format!("(a {first} (b {second} c))");
+                    let groups_left = match outer_level.cmp(&inner_level) {
+                        std::cmp::Ordering::Greater => true,
+                        std::cmp::Ordering::Less => false,
+                        std::cmp::Ordering::Equal => match assoc {
+                            Assoc::Left => true,
+                            Assoc::Right => false,
+                            Assoc::None => {
+                                let result = parse_rendered(&code);
+                                assert!(
+                                    result
+                                        .as_ref()
+                                        .is_err_and(|err| err.starts_with("NonAssociativeOperator")),
+                                    "{code}: {result:?}"
+                                );
+                                continue;
+                            }
+                        },
+                    };
+                    let result = parse_rendered(&code);
+                    if groups_left && is_assignment(second) {
+                        assert!(
+                            result
+                                .as_ref()
+                                .is_err_and(|err| err.starts_with("InvalidAssignmentTarget")),
+                            "{code}: {result:?}"
+                        );
+                    } else if groups_left {
+                        assert_eq!(result, Ok(left()), "{code}");
+                    } else {
+                        assert_eq!(result, Ok(right), "{code}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn prefix_operators_bind_tighter_than_binary_operators() {
+    for prefix in ["-", "!", "~"] {
+        for (_, operators) in LEVELS {
+            for operator in *operators {
+                let code = format!("{prefix}a {operator} b;");
+                let result = parse_rendered(&code);
+                if LEVELS[0].1.contains(operator) {
+                    assert!(
+                        result
+                            .as_ref()
+                            .is_err_and(|err| err.starts_with("InvalidAssignmentTarget")),
+                        "{code}: {result:?}"
+                    );
+                } else {
+                    assert_eq!(result, Ok(format!("(({prefix}a) {operator} b)")), "{code}");
+                }
+            }
+        }
+    }
+
+    let cases = [
+        ("- -a;", "(-(-a))"),
+        ("!!a;", "(!(!a))"),
+        ("-a++;", "(-(a++))"),
+        ("-f(a) * b;", "((-f(a)) * b)"),
+        ("++a * b;", "((++a) * b)"),
+        ("a++ * b;", "((a++) * b)"),
+        ("a * b++;", "(a * (b++))"),
+        ("(a + b) * c;", "((a + b) * c)"),
+        ("a == (b == c);", "(a == (b == c))"),
+    ];
+    for (code, expected) in cases {
+        assert_eq!(parse_rendered(code), Ok(expected.to_string()), "{code}");
+    }
+}
+
+#[test]
+fn errors_point_at_the_offending_token() {
+    let Err(ParserErrors::UnexpectedTokenKind(token)) = parse("let a = 1;\n  let = 2;") else {
+        panic!("expected an unexpected token error");
+    };
+    assert_eq!(
+        (token.kind, token.line, token.pos),
+        (TokenKind::Assignment, 2, 7)
+    );
+}
+
+#[test]
+fn nodes_span_their_source() {
+    let source =
+        "let a = (1 + 2) * foo(3);\nif a { b = -a; } else { c; }\nlet d = if a { 1 } else { 2 };";
+    let statements = Parser::parse(Lexer::tokenize_file(source.to_string(), 3).unwrap()).unwrap();
+    let text = |span: Span| {
+        assert_eq!(span.file, 3);
+        &source[span.start..span.end]
+    };
+
+    let [declaration, branch, conditional] = statements.as_slice() else {
+        panic!("expected three statements, got {statements:?}");
+    };
+    assert_eq!(text(declaration.span), "let a = (1 + 2) * foo(3);");
+    let StatementKind::Variable(_, _, _, _, Some(product)) = &declaration.kind else {
+        panic!("expected an initialized variable, got {declaration:?}");
+    };
+    assert_eq!(text(product.span), "(1 + 2) * foo(3)");
+    let ExpressionKind::Binary(group, _, call) = &product.kind else {
+        panic!("expected a product, got {product:?}");
+    };
+    assert_eq!(text(group.span), "(1 + 2)");
+    assert_eq!(text(call.span), "foo(3)");
+    let ExpressionKind::FunctionCall(callee, arguments) = &call.kind else {
+        panic!("expected a call, got {call:?}");
+    };
+    assert_eq!((text(callee.span), text(arguments[0].span)), ("foo", "3"));
+
+    assert_eq!(text(branch.span), "if a { b = -a; } else { c; }");
+    let StatementKind::If(condition, body, Some(otherwise)) = &branch.kind else {
+        panic!("expected an if statement, got {branch:?}");
+    };
+    assert_eq!(text(condition.span), "a");
+    assert_eq!(text(body[0].span), "b = -a;");
+    assert_eq!(text(otherwise.span), "{ c; }");
+    let StatementKind::Expression(assignment) = &body[0].kind else {
+        panic!("expected an assignment, got {:?}", body[0]);
+    };
+    let ExpressionKind::Assignment(target, value) = &assignment.kind else {
+        panic!("expected an assignment, got {assignment:?}");
+    };
+    assert_eq!(
+        (text(assignment.span), text(target.span), text(value.span)),
+        ("b = -a", "b", "-a")
+    );
+
+    let StatementKind::Variable(_, _, _, _, Some(if_expression)) = &conditional.kind else {
+        panic!("expected an initialized variable, got {conditional:?}");
+    };
+    assert_eq!(text(if_expression.span), "if a { 1 } else { 2 }");
+    let ExpressionKind::If(_, body, otherwise) = &if_expression.kind else {
+        panic!("expected an if expression, got {if_expression:?}");
+    };
+    assert_eq!((text(body.span), text(otherwise.span)), ("{ 1 }", "{ 2 }"));
+}
+
+#[test]
+fn if_expressions_inside_blocks() {
+    let initializer = |code: &str| match parse(code).unwrap().as_slice() {
+        [
+            Statement {
+                kind: StatementKind::Variable(_, _, _, _, Some(value)),
+                ..
+            },
+        ] => render(value),
+        statements => panic!("{code}: expected a single declaration, got {statements:?}"),
+    };
+    assert_eq!(
+        initializer("let x = if a { if b { 1 } else { 2 } } else { 3 };"),
+        "if a { if b { 1 } else { 2 } } else { 3 }"
+    );
+    assert_eq!(
+        initializer("let x = if a { if b { 1 } else if c { 2 } else { 3 } } else { 4 };"),
+        "if a { if b { 1 } else if c { 2 } else { 3 } } else { 4 }"
+    );
+    assert_eq!(
+        initializer("let x = if a { if b { 1 } else { 2 }; 3 } else { 4 };"),
+        "if a { if b { 1 } else { 2 }; 3 } else { 4 }"
+    );
+    // Without an `else` a leading `if` is a statement of the block, `;` is optional.
+    assert_eq!(
+        initializer("let x = if a { if b { c = 1; } if b { c = 2; }; c } else { 3 };"),
+        "if a { if b { (c = 1); } else {}; if b { (c = 2); } else {}; c } else { 3 }"
+    );
+    assert!(matches!(
+        parse("let x = if a { 1 };"),
+        Err(ParserErrors::UnexpectedTokenKind(_))
+    ));
+}
+
+/// Parses with error recovery, returning a summary of each top level statement
+/// and the tokens the errors were reported at.
+fn parse_recovering(code: &str) -> (Vec<String>, Vec<String>) {
+    let (statements, errors) = Parser::parse_recovering(Lexer::tokenize(code.to_string()).unwrap());
+    let statements = statements
+        .iter()
+        .map(|statement| match &statement.kind {
+            StatementKind::Variable(name, _, _, _, Some(value)) => match value.kind {
+                ExpressionKind::Error => format!("let {} = <error>", name.value),
+                _ => format!("let {}", name.value),
+            },
+            StatementKind::Function(name, _, _, body) => format!("fn {name} ({})", body.len()),
+            StatementKind::Error => "<error>".to_string(),
+            kind => format!("{kind:?}"),
+        })
+        .collect();
+    let errors = errors
+        .iter()
+        .map(|error| match error {
+            ParserErrors::NoFunctionHandler(token) | ParserErrors::UnexpectedTokenKind(token) => {
+                format!("{}:{} {:?}", token.line, token.pos, token.kind)
+            }
+            error => format!("{error:?}"),
+        })
+        .collect();
+    (statements, errors)
+}
+
+#[test]
+fn recovers_at_the_next_statement() {
+    let (statements, errors) = parse_recovering(
+        "let a = 1 +;\nlet b = 2;\nfoo(1, ;\nlet c = 3\nlet d = 4;\n}\nlet e = a;",
+    );
+    assert_eq!(
+        statements,
+        [
+            "let a = <error>",
+            "let b",
+            "<error>",
+            "let c",
+            "let d",
+            "<error>",
+            "let e"
+        ]
+    );
+    assert_eq!(
+        errors,
+        [
+            "1:12 SemiColon",
+            "3:8 SemiColon",
+            "5:1 Let",
+            "6:1 CloseCurly"
+        ]
+    );
+}
+
+#[test]
+fn recovers_inside_blocks() {
+    let (statements, errors) =
+        parse_recovering("fn f(): i32 {\n  let x = ;\n  x = ) + 1;\n  return 1\n}\nlet y = f();");
+    assert_eq!(statements, ["fn f (3)", "let y"]);
+    assert_eq!(
+        errors,
+        ["2:11 SemiColon", "3:7 CloseParen", "5:1 CloseCurly"]
+    );
+
+    let (statements, errors) =
+        parse_recovering("let a = if true { let b = 1 2; b } else { 0 };\nlet c = 1;");
+    assert_eq!(statements, ["let a", "let c"]);
+    assert_eq!(errors, ["1:29 Number"]);
+
+    let (statements, errors) = parse_recovering("fn f() {\n  let x = 1;");
+    assert_eq!(statements, ["<error>"]);
+    assert_eq!(errors, ["2:13 Eof"]);
+}
+
+#[test]
+fn skips_the_body_of_a_broken_function_header() {
+    let (statements, errors) =
+        parse_recovering("fn f(a: i32 {\n  if a < 0 { return 0; }\n  return a;\n}\nlet b = 1;");
+    assert_eq!(statements, ["<error>", "let b"]);
+    assert_eq!(errors, ["1:13 OpenCurly"]);
+
+    let (statements, errors) = parse_recovering("fn f(a: i32\nlet b = 1;");
+    assert_eq!(statements, ["<error>", "let b"]);
+    assert_eq!(errors, ["2:1 Let"]);
+
+    let (statements, errors) = parse_recovering("let a = ) return a;\nlet b = 1;");
+    assert_eq!(statements, ["let a = <error>", "let b"]);
+    assert_eq!(errors, ["1:9 CloseParen"]);
+}
+
+#[test]
+fn parse_returns_the_first_error() {
+    let tokens = Lexer::tokenize("let a = ;\nlet b = );".to_string()).unwrap();
+    let Err(ParserErrors::NoFunctionHandler(token)) = Parser::parse(tokens) else {
+        panic!("expected a syntax error");
+    };
+    assert_eq!((token.line, token.pos), (1, 9));
+}
@@ -0,0 +1,157 @@
+use event_script::event_script::{
+    compiler::Compiler,
+    diagnostic::{Diagnostic, SourceMap},
+    interpreter::Interpreter,
+    parser::{MAX_NESTING_DEPTH, Parser, ParserErrors},
+    tokenizer::Lexer,
+    type_system::TypeChecker,
+    value::{IntegerType, Value},
+    vm::Vm,
+};
+
+/// Runs source through the lexer, parser and type checker the way the fuzz
+/// target does and returns the rendered errors.
+fn pipeline(source: &str) -> Vec<String> {
+    let mut files = SourceMap::new();
+    let file = files.add("test.es", source.to_string());
+    let tokens = match Lexer::tokenize_file(source.to_string(), file) {
+        Ok(tokens) => tokens,
+        Err(error) => return vec![Diagnostic::from(&error).render(&files)],
+    };
+    let (statements, errors) = Parser::parse_recovering(tokens);
+    let mut rendered: Vec<_> = errors
+        .iter()
+        .map(|error| Diagnostic::from(error).render(&files))
+        .collect();
+    if let Err(error) = TypeChecker::check(statements) {
+        rendered.push(Diagnostic::from(&error).render(&files));
+    }
+    rendered
+}
+
+fn codes(source: &str) -> Vec<String> {
+    pipeline(source)
+        .iter()
+        .map(|rendered| rendered[6..11].to_string())
+        .collect()
+}
+
+#[test]
+fn regression_corpus() {
+    let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/regressions");
+    let mut cases = 0;
+    for entry in std::fs::read_dir(directory).unwrap() {
+        let source = std::fs::read(entry.unwrap().path()).unwrap();
+        pipeline(&String::from_utf8_lossy(&source));
+        cases += 1;
+    }
+    assert!(cases > 0);
+}
+
+#[test]
+fn reported_crashes_are_errors() {
+    assert_eq!(codes("let x = ;"), ["E0201"]);
+    assert_eq!(codes("{ let a = 1;"), ["E0206"]);
+    assert_eq!(codes("fn f() { let a = 1;"), ["E0206"]);
+    assert_eq!(codes("let"), ["E0206"]);
+    assert_eq!(codes("}"), ["E0201"]);
+    assert_eq!(codes(&format!("{}#", " ".repeat(70_000))), ["E0101"]);
+    assert!(Parser::parse(Vec::new()).unwrap().is_empty());
+    assert!(pipeline("let s: string = \"a\" + \"b\"; let t = s == \"ab\";").is_empty());
+}
+
+#[test]
+fn deep_nesting_is_an_error() {
+    let depth = 10 * MAX_NESTING_DEPTH;
+    let too_deep = [
+        format!("let a = {}1{};", "(".repeat(depth), ")".repeat(depth)),
+        format!("let a = {}1;", "-".repeat(depth)),
+        "{".repeat(depth),
+        format!("fn f() {}", "{ if true ".repeat(depth)),
+    ];
+    for source in too_deep {
+        assert_eq!(codes(&source), ["E0209"]);
+    }
+}
+
+#[test]
+fn nesting_up_to_the_limit_runs() {
+    // The `let` around each expression counts as a level, its initializer and
+    // every right operand too.
+    let depth = MAX_NESTING_DEPTH - 2;
+    let source = format!(
+        "let a = {}1{};\nlet b = 1{};\n{}let c = a + b;{}",
+        "-(".repeat(depth / 2),
+        ")".repeat(depth / 2),
+        " + 1".repeat(depth),
+        "{ ".repeat(depth - 1),
+        " }".repeat(depth - 1),
+    );
+    assert!(pipeline(&source).is_empty());
+    let statements = Parser::parse(Lexer::tokenize(source).unwrap()).unwrap();
+    Interpreter::new().run(&statements).unwrap();
+
+    let source = format!(
+        "let a = {}1{};",
+        "(".repeat(MAX_NESTING_DEPTH),
+        ")".repeat(MAX_NESTING_DEPTH)
+    );
+    let tokens = Lexer::tokenize(source).unwrap();
+    assert!(matches!(
+        Parser::parse(tokens),
+        Err(ParserErrors::NestingTooDeep(_))
+    ));
+}
+
+#[test]
+fn long_operator_chains_run() {
+    // Chains of binary operations don't nest, however long they are. Every pass
+    // over the tree, dropping and comparing it too, walks them without recursing.
+    let terms = 1024 * MAX_NESTING_DEPTH;
+    let source = format!(
+        "let a = 0{};\nlet b = a > 0{};",
+        " + 1".repeat(terms),
+        " && a > 0".repeat(terms),
+    );
+    assert!(pipeline(&source).is_empty());
+    let statements = Parser::parse(Lexer::tokenize(source).unwrap()).unwrap();
+    let statements = TypeChecker::check(statements).unwrap();
+    assert!(statements.clone() == statements);
+    let mut interpreter = Interpreter::new();
+    interpreter.run(&statements).unwrap();
+    let terms = i128::try_from(terms).unwrap();
+    assert_eq!(
+        interpreter.get_variable("a"),
+        Ok(&Value::Integer(terms, IntegerType::I32))
+    );
+    assert_eq!(interpreter.get_variable("b"), Ok(&Value::Bool(true)));
+    Vm::new(Compiler::compile(&statements).unwrap())
+        .run()
+        .unwrap();
+}
+
+/// Glues random fragments of the language together, a cheap stand-in for the
+/// fuzz target that runs with the rest of the tests.
+#[test]
+fn random_fragments() {
+    const FRAGMENTS: [&str; 40] = [
+        "let ", "mut ", "const ", "fn ", "if ", "else ", "while ", "for ", "in ", "return ",
+        "break", "continue", "a", "b", "f", "i32", "u8", "1", "300", "1.5", "\"s\"", "true", "(",
+        ")", "{", "}", ";", ":", ",", "=", "+", "-", "<", "<<", "..", "!", "~", "++", "&&", " ",
+    ];
+    // xorshift, deterministic so failures reproduce.
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        usize::try_from(state % 1024).unwrap()
+    };
+    for _ in 0..2000 {
+        let length = next() % 40;
+        let source: String = (0..length)
+            .map(|_| FRAGMENTS[next() % FRAGMENTS.len()])
+            .collect();
+        pipeline(&source);
+    }
+}
@@ -0,0 +1,203 @@
+use event_script::event_script::{
+    span::Span,
+    token::TokenKind,
+    tokenizer::{Lexer, TokenizerError},
+};
+
+fn lex(code: &str) -> Vec<(TokenKind, String)> {
+    Lexer::tokenize(code.to_string())
+        .unwrap()
+        .into_iter()
+        .map(|token| (token.kind, token.value))
+        .collect()
+}
+
+fn token(kind: TokenKind, value: &str) -> (TokenKind, String) {
+    (kind, value.to_string())
+}
+
+#[test]
+fn number_literals() {
+    assert_eq!(
+        lex("1 1.5 2e10 1e-3 6.02E+23 0..10"),
+        vec![
+            token(TokenKind::Number, "1"),
+            token(TokenKind::Float, "1.5"),
+            token(TokenKind::Float, "2e10"),
+            token(TokenKind::Float, "1e-3"),
+            token(TokenKind::Float, "6.02E+23"),
+            token(TokenKind::Number, "0"),
+            token(TokenKind::DotDot, ".."),
+            token(TokenKind::Number, "10"),
+            token(TokenKind::Eof, ""),
+        ]
+    );
+}
+
+#[test]
+fn integer_literal_forms() {
+    let tokens =
+        Lexer::tokenize("0xff 0b1010 0o17 1_000_000 255u8 0x10i64 1_000.5".to_string()).unwrap();
+    let literals: Vec<_> = tokens
+        .iter()
+        .map(|token| {
+            (
+                token.kind.clone(),
+                token.value.as_str(),
+                token.suffix.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        literals,
+        vec![
+            (TokenKind::Number, "255", None),
+            (TokenKind::Number, "10", None),
+            (TokenKind::Number, "15", None),
+            (TokenKind::Number, "1000000", None),
+            (TokenKind::Number, "255", Some("u8")),
+            (TokenKind::Number, "16", Some("i64")),
+            (TokenKind::Float, "1000.5", None),
+            (TokenKind::Eof, "", None),
+        ]
+    );
+}
+
+#[test]
+fn bitwise_operators() {
+    assert_eq!(
+        lex("a || b | c && d & e ^ ~f << g >> h <= i"),
+        vec![
+            token(TokenKind::Identifier, "a"),
+            token(TokenKind::Or, "||"),
+            token(TokenKind::Identifier, "b"),
+            token(TokenKind::Pipe, "|"),
+            token(TokenKind::Identifier, "c"),
+            token(TokenKind::And, "&&"),
+            token(TokenKind::Identifier, "d"),
+            token(TokenKind::Ampersand, "&"),
+            token(TokenKind::Identifier, "e"),
+            token(TokenKind::Caret, "^"),
+            token(TokenKind::Tilde, "~"),
+            token(TokenKind::Identifier, "f"),
+            token(TokenKind::ShiftLeft, "<<"),
+            token(TokenKind::Identifier, "g"),
+            token(TokenKind::ShiftRight, ">>"),
+            token(TokenKind::Identifier, "h"),
+            token(TokenKind::LessEquals, "<="),
+            token(TokenKind::Identifier, "i"),
+            token(TokenKind::Eof, ""),
+        ]
+    );
+}
+
+#[test]
+fn longest_match_wins() {
+    assert_eq!(
+        lex("a|||b ...= ::: <<= !== === +++ 1.5e3 returned"),
+        vec![
+            token(TokenKind::Identifier, "a"),
+            token(TokenKind::Or, "||"),
+            token(TokenKind::Pipe, "|"),
+            token(TokenKind::Identifier, "b"),
+            token(TokenKind::DotDot, ".."),
+            token(TokenKind::Dot, "."),
+            token(TokenKind::Assignment, "="),
+            token(TokenKind::DoubleColon, "::"),
+            token(TokenKind::Colon, ":"),
+            token(TokenKind::ShiftLeft, "<<"),
+            token(TokenKind::Assignment, "="),
+            token(TokenKind::NotEquals, "!="),
+            token(TokenKind::Assignment, "="),
+            token(TokenKind::Equals, "=="),
+            token(TokenKind::Assignment, "="),
+            token(TokenKind::PlusPlus, "++"),
+            token(TokenKind::Plus, "+"),
+            token(TokenKind::Float, "1.5e3"),
+            token(TokenKind::Identifier, "returned"),
+            token(TokenKind::Eof, ""),
+        ]
+    );
+}
+
+#[test]
+fn token_locations() {
+    let source = "let a = 1;\n// comment é\nlet s = \"é\nx\"; b";
+    let tokens = Lexer::tokenize(source.to_string()).unwrap();
+    let locations: Vec<_> = tokens
+        .iter()
+        .map(|token| {
+            (
+                token.value.as_str(),
+                token.line,
+                token.pos,
+                token.start,
+                token.end,
+            )
+        })
+        .collect();
+    assert_eq!(
+        locations,
+        vec![
+            ("let", 1, 1, 0, 3),
+            ("a", 1, 5, 4, 5),
+            ("=", 1, 7, 6, 7),
+            ("1", 1, 9, 8, 9),
+            (";", 1, 10, 9, 10),
+            ("let", 3, 1, 25, 28),
+            ("s", 3, 5, 29, 30),
+            ("=", 3, 7, 31, 32),
+            ("é\nx", 3, 9, 33, 39),
+            (";", 4, 3, 39, 40),
+            ("b", 4, 5, 41, 42),
+            ("", 4, 6, 42, 42),
+        ]
+    );
+    for token in &tokens[..tokens.len() - 1] {
+        let text = &source[token.start..token.end];
+        assert!(text.contains(token.value.as_str()), "{text:?}");
+    }
+}
+
+#[test]
+fn invalid_token_location() {
+    let error = Lexer::tokenize_file("let a = \"é\";\n  x # 2".to_string(), 1).unwrap_err();
+    assert!(matches!(
+        error,
+        TokenizerError::InvalidToken('#', span) if span == Span::new(1, 18, 19)
+    ));
+
+    let error = Lexer::tokenize("a = \"abc;".to_string()).unwrap_err();
+    assert!(matches!(
+        error,
+        TokenizerError::UnterminatedString(span) if span == Span::new(0, 4, 5)
+    ));
+}
+
+#[test]
+fn invalid_literal_suffixes() {
+    let suffix = |code: &str| match Lexer::tokenize(code.to_string()).unwrap_err() {
+        TokenizerError::InvalidSuffix(suffix, span) => (suffix, span.start, span.end),
+        error => panic!("{code}: expected an invalid suffix, got {error:?}"),
+    };
+    assert_eq!(suffix("let a = 10u8x;"), ("u8x".to_string(), 10, 13));
+    assert_eq!(suffix("10u128"), ("u128".to_string(), 2, 6));
+    assert_eq!(suffix("0xffg"), ("g".to_string(), 4, 5));
+    assert_eq!(suffix("0b102"), ("2".to_string(), 4, 5));
+    assert_eq!(suffix("1.5f32"), ("f32".to_string(), 3, 6));
+    assert_eq!(suffix("1e"), ("e".to_string(), 1, 2));
+
+    assert_eq!(
+        lex("10u8+x 1i64..2;"),
+        vec![
+            token(TokenKind::Number, "10"),
+            token(TokenKind::Plus, "+"),
+            token(TokenKind::Identifier, "x"),
+            token(TokenKind::Number, "1"),
+            token(TokenKind::DotDot, ".."),
+            token(TokenKind::Number, "2"),
+            token(TokenKind::SemiColon, ";"),
+            token(TokenKind::Eof, ""),
+        ]
+    );
+}
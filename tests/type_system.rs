@@ -0,0 +1,491 @@
+use event_script::event_script::{
+    parser::Parser,
+    tokenizer::Lexer,
+    type_system::{TypeChecker, TypeErrors},
+};
+
+fn check(code: &str) -> Result<(), TypeErrors> {
+    let tokens = Lexer::tokenize(code.to_string()).unwrap();
+    TypeChecker::check(Parser::parse(tokens).unwrap())
+        .map(|_| ())
+        .map_err(|error| *error.kind)
+}
+
+#[test]
+fn functions() {
+    assert!(check("fn add(a: i8, b: i8): i8 { return a + b; } let c = add(1, 2);").is_ok());
+    assert!(check("fn f(a: i8): i8 { return f(a); }").is_ok());
+    assert!(matches!(
+        check("fn f(a: i8) { } let c = f(1, 2);"),
+        Err(TypeErrors::ArgumentCountMismatch(_, 1, 2))
+    ));
+    assert!(matches!(
+        check("fn f(a: i8) { } let c = f(300);"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("fn f(a: i8) { } let c = f(1i16);"),
+        Err(TypeErrors::ArgumentTypeMismatch(..))
+    ));
+    assert!(matches!(
+        check("fn f(): i8 { return 1i64; }"),
+        Err(TypeErrors::ReturnTypeMismatch(..))
+    ));
+    assert!(matches!(
+        check("fn f(): i8 { }"),
+        Err(TypeErrors::MissingReturn(_))
+    ));
+    assert!(matches!(
+        check("return;"),
+        Err(TypeErrors::ReturnOutsideFunction())
+    ));
+    assert!(matches!(
+        check("let a = 1; let b = a(2);"),
+        Err(TypeErrors::SymbolIsNotAFunction(_))
+    ));
+}
+
+#[test]
+fn function_scopes() {
+    assert!(check("let g = 1; fn f(): i32 { return g; }").is_ok());
+    assert!(matches!(
+        check("{ let local = 1; fn f(): i8 { return local; } }"),
+        Err(TypeErrors::VariableDoesntExist(_))
+    ));
+    assert!(matches!(
+        check("fn f(a: i8) { } let b = a;"),
+        Err(TypeErrors::VariableDoesntExist(_))
+    ));
+}
+
+#[test]
+fn conditionals() {
+    assert!(check("let a = 1; if a < 2 { let b = a; } else if a == 2 { } else { }").is_ok());
+    assert!(check("let a = 1; let b = if a < 2 { a } else { 3 };").is_ok());
+    assert!(check("let a = 1; let b = if a < 2 { if a < 1 { 1 } else { 2 } } else { 3 };").is_ok());
+    assert!(
+        check("let a = 1; let mut b = 0; let c = if a < 2 { if a < 1 { b = 1; } b } else { 2 };")
+            .is_ok()
+    );
+    assert!(check("fn f(a: i8): i8 { if a < 0 { return 0; } else { return a; } }").is_ok());
+    assert!(matches!(
+        check("let a = 1; if a { }"),
+        Err(TypeErrors::ConditionIsNotABool(_))
+    ));
+    assert!(matches!(
+        check("let a = 1; let b = if a < 2 { a } else { 1u8 };"),
+        Err(TypeErrors::BranchTypeMismatch(..))
+    ));
+    assert!(matches!(
+        check("let a = 1; let b = if a < 2 { if a < 1 { 1 } } else { 2 };"),
+        Err(TypeErrors::BranchTypeMismatch(..))
+    ));
+    assert!(matches!(
+        check("fn f(a: i8): i8 { if a < 0 { return 0; } }"),
+        Err(TypeErrors::MissingReturn(_))
+    ));
+    assert!(matches!(
+        check("fn f(a: i8): i8 { let b = if a < 0 { return 0; } else { a }; return b; }"),
+        Err(TypeErrors::ReturnInsideExpression())
+    ));
+}
+
+#[test]
+fn loops() {
+    assert!(check("let a = 1; while a < 2 { if a == 1 { continue; } break; }").is_ok());
+    assert!(matches!(
+        check("let a = 1; while a { }"),
+        Err(TypeErrors::ConditionIsNotABool(_))
+    ));
+    assert!(matches!(
+        check("break;"),
+        Err(TypeErrors::BreakOutsideLoop())
+    ));
+    assert!(matches!(
+        check("let a = 1; if a < 2 { continue; }"),
+        Err(TypeErrors::ContinueOutsideLoop())
+    ));
+    assert!(matches!(
+        check("let a = 1; while a < 2 { fn f() { break; } }"),
+        Err(TypeErrors::BreakOutsideLoop())
+    ));
+    assert!(matches!(
+        check("let a = 1; while a < 2 { let b = if a < 2 { break; } else { }; }"),
+        Err(TypeErrors::BreakOutsideLoop())
+    ));
+}
+
+#[test]
+fn for_loops() {
+    assert!(
+        check("fn f(n: i8): i8 { for i in 0..=n { if i == n { return i; } } return n; }").is_ok()
+    );
+    assert!(check("for i in 0..10 { if i < 5 { continue; } break; }").is_ok());
+    assert!(matches!(
+        check("for i in 0..10 { } let j = i;"),
+        Err(TypeErrors::VariableDoesntExist(_))
+    ));
+    assert!(matches!(
+        check("let a = 5; for i in a { }"),
+        Err(TypeErrors::TypeIsNotIterable(_))
+    ));
+    assert!(matches!(
+        check("let r = (1 < 2)..(2 < 3);"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+}
+
+#[test]
+fn booleans() {
+    assert!(
+        check("let a = true; let b = !a && false; let c = 1 < 2 && a; let d = a == b;").is_ok()
+    );
+    assert!(check("let a = 1; let b = -a; if a == 1 && true { }").is_ok());
+    assert!(matches!(
+        check("let a = 1 && 2;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let a = !1;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let a = true + false;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let a = true < false;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+}
+
+#[test]
+fn floats() {
+    assert!(check("let a = 1.5; let b = a * 2.0 - 1e-3; let c = b < a; let d = -b;").is_ok());
+    assert!(matches!(
+        check("let a = 1.5 + 1;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+}
+
+#[test]
+fn integer_literal_suffixes() {
+    assert!(check("let a = 255u8; let b = a + 1u8; let c = 0x10i64 * 0b11i64;").is_ok());
+    assert!(matches!(
+        check("let a = 256u8;"),
+        Err(TypeErrors::LiteralOutOfRange(_, _))
+    ));
+    assert!(matches!(
+        check("let a = 1u8 + 1i64;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+}
+
+#[test]
+fn contextual_integer_literals() {
+    assert!(check("let a = 5; let b = a + 300; let c = 100000 * a;").is_ok());
+    assert!(check("let a: u8 = 255; let b = 1 + a; let c = a < 2;").is_ok());
+    assert!(check("let a: i8 = -128; let b: u64 = 18446744073709551615;").is_ok());
+    assert!(check("fn f(a: u16): u16 { return a + 1; } let b = f(65535);").is_ok());
+    assert!(check("let n: u8 = 10; for i in 0..n { let j: u8 = i; }").is_ok());
+    assert!(matches!(
+        check("let a: u8 = 256;"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let a: i8 = -129;"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let a = 3000000000;"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let a: u8 = 1; let b = a + 256;"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+}
+
+#[test]
+fn variable_annotations() {
+    assert!(check("let a: i32 = 1; let b: bool = a < 2; let c: f64 = 1.5; let d: u8;").is_ok());
+    assert!(check("let a: i64 = 1; let b = a + 1i64;").is_ok());
+    assert!(matches!(
+        check("let a: bool = 1;"),
+        Err(TypeErrors::Mismatch { .. })
+    ));
+    assert!(matches!(
+        check("let a = 1; let b: i64 = a;"),
+        Err(TypeErrors::Mismatch { .. })
+    ));
+    assert!(matches!(
+        check("let a: u8 = 300;"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let a: nothing = 1;"),
+        Err(TypeErrors::TypeNotFound(_))
+    ));
+    assert!(matches!(
+        check("let a = 1; let b: a = 1;"),
+        Err(TypeErrors::SymbolIsNotAType(..))
+    ));
+}
+
+#[test]
+fn uninitialized_variable_inference() {
+    assert!(check("let a; a = 1u8; let b: u8 = a + 1;").is_ok());
+    assert!(check("let a; a = 5; let b: u8 = a;").is_ok());
+    assert!(check("let a; a = true; if a { }").is_ok());
+    assert!(check("let a; if true { a = 1i64; } else { a = 2; } let b: i64 = a;").is_ok());
+    assert!(check("let a; let b: u16 = 1; a = b;").is_ok());
+    assert!(check("fn f(x: u64) { } let a; a = 3; f(a);").is_ok());
+    assert!(check("let a; a = 3000000000; let r: u64 = a;").is_ok());
+    assert!(matches!(check("let a;"), Err(TypeErrors::AmbiguousType(_))));
+    assert!(matches!(
+        check("{ let a; }"),
+        Err(TypeErrors::AmbiguousType(_))
+    ));
+    assert!(matches!(
+        check("let a; a = 300; let b: u8 = a;"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let a; a = 3000000000; let b = a;"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("{ let a; a = 3000000000; }"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let a; a = 1; let b = a; let c: u8 = a;"),
+        Err(TypeErrors::Mismatch { .. })
+    ));
+    assert!(matches!(
+        check("let mut a; a = 1u8; a = true;"),
+        Err(TypeErrors::Mismatch { .. })
+    ));
+}
+
+#[test]
+fn definite_assignment() {
+    assert!(check("let a: i32; if true { a = 1; } else { a = 2; } let b = a;").is_ok());
+    assert!(
+        check("fn f(c: bool): i32 { let a: i32; if c { a = 1; } else { return 0; } return a; }")
+            .is_ok()
+    );
+    assert!(check("let a: i32; { a = 1; } let b = a + 1;").is_ok());
+    assert!(check("let a: bool; let b = true && if true { a = false; a } else { false };").is_ok());
+    assert!(check("const a: i32; if true { a = 1; } else { a = 2; }").is_ok());
+    assert!(check("while true { const a: i32; a = 1; }").is_ok());
+
+    let Err(TypeErrors::UseOfPossiblyUninitialized(token)) = check("let a: i32;\nlet b = a;")
+    else {
+        panic!("reading an unassigned variable must fail");
+    };
+    assert_eq!((token.value.as_str(), token.line, token.pos), ("a", 2, 9));
+    assert!(matches!(
+        check("let a: i32; if true { a = 1; } let b = a;"),
+        Err(TypeErrors::UseOfPossiblyUninitialized(_))
+    ));
+    assert!(matches!(
+        check("let mut a: i32; while true { a = 1; } let b = a;"),
+        Err(TypeErrors::UseOfPossiblyUninitialized(_))
+    ));
+    assert!(matches!(
+        check("let a: bool; let b = false && if true { a = true; a } else { false }; let c = a;"),
+        Err(TypeErrors::UseOfPossiblyUninitialized(_))
+    ));
+    assert!(matches!(
+        check("let a: i32; fn f(): i32 { return a; }"),
+        Err(TypeErrors::UseOfPossiblyUninitialized(_))
+    ));
+    assert!(matches!(
+        check("const a: i32; a = 1; a = 2;"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("const a: i32; if true { a = 1; } a = 2;"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("const a: i32; for i in 0..2 { a = i; }"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("const a: i32; fn f() { a = 1; }"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("const a = 1; a = 2;"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("{ const a: i32; }"),
+        Err(TypeErrors::ConstNeverAssigned(_))
+    ));
+}
+
+#[test]
+fn mutability() {
+    assert!(check("let mut a = 1; a = 2; a = a + 1;").is_ok());
+    assert!(check("let mut a = 1; while a < 10 { a = a * 2; }").is_ok());
+    assert!(check("let a: i32; a = 1;").is_ok());
+
+    let Err(TypeErrors::AssignmentToImmutable {
+        target,
+        declaration,
+        help,
+    }) = check("let a = 1; a = 2;")
+    else {
+        panic!("assigning an immutable variable must fail");
+    };
+    assert_eq!(target.value, "a");
+    assert_eq!(declaration.map(|token| token.value), Some("a".to_string()));
+    assert!(help.contains("let mut a"));
+
+    assert!(matches!(
+        check("let a: i32; a = 1; a = 2;"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("let a: i32; while true { a = 1; }"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("fn f(a: i32) { a = 2; }"),
+        Err(TypeErrors::AssignmentToImmutable {
+            declaration: None,
+            ..
+        })
+    ));
+    assert!(matches!(
+        check("for i in 0..3 { i = 1; }"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+}
+
+#[test]
+fn compound_assignments() {
+    assert!(check("let mut a = 1; a += 2; a *= a; a++; --a;").is_ok());
+    assert!(check("let mut a: u8 = 1; a += 255; let b: u8 = a++;").is_ok());
+    assert!(check("let mut a = 1.5; a /= 2.0; a--;").is_ok());
+    assert!(matches!(
+        check("let a = 1; a += 1;"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("let a = 1; a++;"),
+        Err(TypeErrors::AssignmentToImmutable { .. })
+    ));
+    assert!(matches!(
+        check("let mut a = true; a += false;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let mut a = true; a++;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let mut a: u8 = 1; a += 256;"),
+        Err(TypeErrors::LiteralOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let mut a = 1; a += 1.5;"),
+        Err(TypeErrors::Mismatch { .. })
+    ));
+    assert!(matches!(
+        check("let mut a: i32; a += 1;"),
+        Err(TypeErrors::UseOfPossiblyUninitialized(_))
+    ));
+}
+
+#[test]
+fn bitwise_operators() {
+    assert!(check("let a: u8 = 0b1010 | 1 & 0xf ^ ~2; let b = a << 7 >> 1u32;").is_ok());
+    assert!(check("let a = 1i64 << 63; let b: u16 = 1 << 15;").is_ok());
+    assert!(check("let n = 3u8; let a = 1u32 << n;").is_ok());
+    assert!(matches!(
+        check("let a = 1.5 | 2.5;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let a = true & false;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let a = ~true;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let a = 1 << 1.5;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let a = 1u8 | 1u16;"),
+        Err(TypeErrors::OperationIsNotSupported(..))
+    ));
+    assert!(matches!(
+        check("let a = 1u8 << 8;"),
+        Err(TypeErrors::ShiftOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let a = 1 << 32u8;"),
+        Err(TypeErrors::ShiftOutOfRange(..))
+    ));
+    assert!(matches!(
+        check("let a = 1i64 >> -1;"),
+        Err(TypeErrors::ShiftOutOfRange(..))
+    ));
+}
+
+#[test]
+fn redeclarations() {
+    assert!(check("let a = 1; { let a = true; } { let a = \"s\"; } let b = a + 1;").is_ok());
+    assert!(check("fn f(a: i32): i32 { { let a = 2; } return a; }").is_ok());
+
+    assert!(matches!(
+        check("let a = 1; fn f(): i32 { return a; } let a = 2; let r = f();"),
+        Err(TypeErrors::VariableAlreadyExists(name)) if name == "a"
+    ));
+    assert!(matches!(
+        check("let a = 1; fn f(): i32 { return a; } let a = \"s\"; let r = f();"),
+        Err(TypeErrors::VariableAlreadyExists(name)) if name == "a"
+    ));
+    assert!(matches!(
+        check("{ let b = 1; let b = 2; }"),
+        Err(TypeErrors::VariableAlreadyExists(name)) if name == "b"
+    ));
+}
+
+/// Checks the partial AST of a script with syntax errors.
+fn check_recovered(code: &str) -> Result<(), TypeErrors> {
+    let tokens = Lexer::tokenize(code.to_string()).unwrap();
+    let (statements, errors) = Parser::parse_recovering(tokens);
+    assert!(!errors.is_empty(), "{code}: expected a syntax error");
+    TypeChecker::check(statements)
+        .map(|_| ())
+        .map_err(|error| *error.kind)
+}
+
+#[test]
+fn syntax_errors_dont_cause_type_errors() {
+    assert!(check_recovered("let a = 1 +; let b: i32 = a * 2; let c: bool = !a;").is_ok());
+    assert!(check_recovered("let a; a = ); let b: u8 = a; const c; c = );").is_ok());
+    assert!(check_recovered("fn f(): i32 { return ); } let a: i32 = f();").is_ok());
+    assert!(
+        check_recovered("let mut a = ); for i in a { let b = i + 1; } if a { a += 1; }").is_ok()
+    );
+    assert!(check_recovered("let a = if ) { 1 } else { 2 }; let b = a << 3;").is_ok());
+    assert!(check_recovered("fn f(a: i32 { return a; } let b: i32 = f(1) + 1;").is_ok());
+
+    assert!(matches!(
+        check_recovered("let a = ); let b: bool = 1.5;"),
+        Err(TypeErrors::Mismatch { .. })
+    ));
+    assert!(matches!(
+        check_recovered("let a = ); let b = c;"),
+        Err(TypeErrors::VariableDoesntExist(name)) if name == "c"
+    ));
+}